@@ -0,0 +1,332 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Whether an [Object] should be submitted to the forward pass or the deferred
+/// geometry pass when rendered with [RenderTarget::render_deferred].
+/// Objects that report [MaterialType::Transparent] are always forwarded to the
+/// forward pass since the deferred G-buffer has no blending stage.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpaqueRenderMethod {
+    /// Render directly into the target, looping over all lights per object.
+    Forward,
+    /// Render into the G-buffer maintained by [DeferredPipeline] and light in a single full-screen pass.
+    Deferred,
+}
+
+///
+/// The PBR inputs one [DeferredGeometry] submits to the geometry prepass for a single draw call.
+/// `positions` and `normals` are in model space and must have the same length; `model` is applied
+/// by the geometry-pass vertex shader to bring them into world space, matching how a
+/// forward-rendered [Object] applies its own model matrix.
+///
+pub struct GBufferInputs<'a> {
+    /// Model-space vertex positions.
+    pub positions: &'a VertexBuffer,
+    /// Model-space vertex normals.
+    pub normals: &'a VertexBuffer,
+    /// Optional index buffer; `None` draws `positions`/`normals` as a plain triangle list.
+    pub indices: Option<&'a ElementBuffer>,
+    /// Model matrix applied on top of `positions`/`normals`.
+    pub model: Mat4,
+    /// Material constants, assumed uniform over the whole draw call (per-pixel textures are not
+    /// supported by this minimal G-buffer yet).
+    pub material: GBufferMaterial,
+}
+
+/// See [GBufferInputs::material].
+#[derive(Debug, Clone, Copy)]
+pub struct GBufferMaterial {
+    /// Base color / diffuse albedo.
+    pub base_color: Vec3,
+    /// 0 is fully dielectric, 1 is fully metallic.
+    pub metallic: f32,
+    /// GGX roughness, `0` is a mirror.
+    pub roughness: f32,
+    /// Ambient occlusion factor, `1` is fully unoccluded.
+    pub occlusion: f32,
+}
+
+///
+/// Extends [Object] with what [DeferredPipeline::geometry_pass] needs to write this object's
+/// surface properties into the G-buffer: its geometry and material constants, already in the shape
+/// the packing shader expects. Only types that implement this can be submitted to
+/// [DeferredPipeline::geometry_pass] in the first place - there is no implicit fallback to
+/// forward-shading an arbitrary [Object] into the G-buffer target, since a forward material's
+/// fragment shader has no way to emit the packed format [DeferredPipeline] expects.
+///
+pub trait DeferredGeometry: Object {
+    /// Returns this object's surface data for the geometry prepass.
+    fn gbuffer_inputs(&self) -> GBufferInputs;
+}
+
+impl DeferredGeometry for Gm<Mesh, PhysicalMaterial> {
+    fn gbuffer_inputs(&self) -> GBufferInputs {
+        GBufferInputs {
+            positions: &self.geometry.position_buffer,
+            normals: &self.geometry.normal_buffer,
+            indices: self.geometry.index_buffer.as_ref(),
+            model: self.geometry.transformation,
+            material: GBufferMaterial {
+                base_color: self.material.albedo.to_linear_srgb().truncate(),
+                metallic: self.material.metallic,
+                roughness: self.material.roughness,
+                occlusion: self.material.occlusion_strength,
+            },
+        }
+    }
+}
+
+///
+/// Packs the per-pixel inputs to the physically based shading model - base color, world space
+/// normal, metallic, roughness and occlusion - into a single [Rgba32Uint] texture, together with a
+/// matching depth texture, and resolves all lights against that buffer in one full-screen pass.
+///
+/// This is the deferred counterpart to the forward rendering performed directly by [Object::render]:
+/// use [DeferredPipeline::geometry_pass] to fill the G-buffer with all objects whose
+/// [Object::opaque_render_method] is [OpaqueRenderMethod::Deferred], then [DeferredPipeline::lighting_pass]
+/// to shade the whole screen against `lights` in one pass instead of looping over lights per object.
+/// Transparent and forward-only objects must still be rendered on top with the regular forward path.
+///
+pub struct DeferredPipeline {
+    context: Context,
+    geometry_program: Program,
+    gbuffer: Texture2D,
+    depth_texture: DepthTexture2D,
+    width: u32,
+    height: u32,
+}
+
+impl DeferredPipeline {
+    ///
+    /// Creates a new deferred pipeline that can shade a `width` x `height` sized viewport.
+    ///
+    pub fn new(context: &Context, width: u32, height: u32) -> Self {
+        let gbuffer = Texture2D::new_empty::<[u32; 4]>(
+            context,
+            width,
+            height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let depth_texture = DepthTexture2D::new::<f32>(
+            context,
+            width,
+            height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let geometry_program = Program::from_source(
+            context,
+            include_str!("gbuffer_write.vert"),
+            &geometry_fragment_shader_source(),
+        )
+        .expect("gbuffer_write shader failed to compile");
+        Self {
+            context: context.clone(),
+            geometry_program,
+            gbuffer,
+            depth_texture,
+            width,
+            height,
+        }
+    }
+
+    ///
+    /// Resizes the G-buffer to match a new viewport size.
+    ///
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let context = self.context.clone();
+        *self = Self::new(&context, width, height);
+    }
+
+    ///
+    /// Runs the geometry prepass: renders `objects` whose [Object::opaque_render_method] reports
+    /// [OpaqueRenderMethod::Deferred] into the G-buffer, packing base color, world normal and
+    /// metallic/roughness per pixel alongside depth via [DeferredGeometry::gbuffer_inputs]. Objects
+    /// reporting [OpaqueRenderMethod::Forward] or [MaterialType::Transparent] are skipped and must
+    /// be rendered separately by the caller; objects that don't implement [DeferredGeometry] can't
+    /// be passed here at all.
+    ///
+    pub fn geometry_pass<'a>(
+        &self,
+        camera: &'a Camera,
+        objects: impl IntoIterator<Item = &'a dyn DeferredGeometry>,
+    ) -> ThreeDResult<()> {
+        RenderTarget::new(
+            self.gbuffer.as_color_target(None),
+            self.depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+        .write(|| {
+            for object in objects
+                .into_iter()
+                .filter(|o| {
+                    o.opaque_render_method() == OpaqueRenderMethod::Deferred
+                        && o.material_type() != MaterialType::Transparent
+                })
+            {
+                let inputs = object.gbuffer_inputs();
+                self.geometry_program
+                    .use_uniform("viewProjection", camera.projection() * camera.view())?;
+                self.geometry_program.use_uniform("model", inputs.model)?;
+                self.geometry_program.use_uniform(
+                    "normalMatrix",
+                    inputs.model.invert().unwrap_or(Mat4::identity()).transpose(),
+                )?;
+                self.geometry_program
+                    .use_uniform("baseColor", inputs.material.base_color)?;
+                self.geometry_program
+                    .use_uniform("metallic", inputs.material.metallic)?;
+                self.geometry_program
+                    .use_uniform("roughness", inputs.material.roughness)?;
+                self.geometry_program
+                    .use_uniform("occlusion", inputs.material.occlusion)?;
+                self.geometry_program
+                    .use_vertex_attribute("position", inputs.positions)?;
+                self.geometry_program
+                    .use_vertex_attribute("normal", inputs.normals)?;
+
+                let render_states = RenderStates {
+                    depth_test: DepthTest::LessOrEqual,
+                    cull: Cull::Back,
+                    ..Default::default()
+                };
+                match inputs.indices {
+                    Some(indices) => self.geometry_program.draw_elements(
+                        render_states,
+                        camera.viewport(),
+                        indices,
+                    ),
+                    None => self.geometry_program.draw_arrays(
+                        render_states,
+                        camera.viewport(),
+                        inputs.positions.count(),
+                    ),
+                }
+            }
+            Ok(())
+        })
+    }
+
+    ///
+    /// Resolves the G-buffer produced by [Self::geometry_pass] against `lights` in a single
+    /// full-screen pass - unpacking base color/normal/metallic/roughness/occlusion and reconstructing
+    /// world position from depth, then shading with the same PBR lighting model the forward
+    /// `PhysicalMaterial` path uses - accumulating all lights in one shading evaluation instead of
+    /// the per-object light loop the forward path uses, and writes the result into `target`.
+    ///
+    pub fn lighting_pass(
+        &self,
+        target: &RenderTarget,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) -> ThreeDResult<()> {
+        target.write(|| {
+            let lighting_functions = lights_shader_source(
+                lights,
+                LightingModel::Cook(
+                    NormalDistributionFunction::TrowbridgeReitzGGX,
+                    GeometryFunction::SmithSchlickGGX,
+                ),
+            );
+            self.context.program(
+                full_screen_vertex_shader_source(),
+                deferred_lighting_fragment_shader_source(&lighting_functions),
+                |program| {
+                    program.use_texture("gbuffer", &self.gbuffer)?;
+                    program.use_depth_texture("gbufferDepth", &self.depth_texture)?;
+                    program.use_uniform("eyePosition", camera.position())?;
+                    program.use_uniform(
+                        "viewProjectionInverse",
+                        (camera.projection() * camera.view())
+                            .invert()
+                            .unwrap_or(Mat4::identity()),
+                    )?;
+                    for (i, light) in lights.iter().enumerate() {
+                        light.use_uniforms(program, i as u32)?;
+                    }
+                    program.draw_arrays(
+                        RenderStates {
+                            depth_test: DepthTest::Always,
+                            cull: Cull::Back,
+                            ..Default::default()
+                        },
+                        camera.viewport(),
+                        3,
+                    );
+                    Ok(())
+                },
+            )
+        })
+    }
+}
+
+fn geometry_fragment_shader_source() -> String {
+    format!(
+        "{}\n{}",
+        include_str!("gbuffer.frag"),
+        include_str!("gbuffer_write.frag")
+    )
+}
+
+fn deferred_lighting_fragment_shader_source(lighting_functions: &str) -> String {
+    format!(
+        "{}\n{}\n{}",
+        include_str!("gbuffer.frag"),
+        lighting_functions,
+        include_str!("deferred_lighting.frag")
+    )
+}
+
+impl RenderTarget<'_> {
+    ///
+    /// Renders `deferred_objects` and `forward_objects` lit by `lights` using the deferred
+    /// pipeline: `deferred_objects` go through a [DeferredPipeline::geometry_pass] prepass and a
+    /// single screen-space [DeferredPipeline::lighting_pass], then this target's depth buffer is
+    /// seeded from [DeferredPipeline]'s own depth texture so `forward_objects` - and any
+    /// `deferred_objects` entry that reports [OpaqueRenderMethod::Forward] or
+    /// [MaterialType::Transparent] - depth-test against the geometry the prepass actually wrote when they're
+    /// rendered on top, so a scene can freely mix both passes and still occlude correctly.
+    ///
+    pub fn render_deferred<'a>(
+        &self,
+        pipeline: &mut DeferredPipeline,
+        camera: &'a Camera,
+        deferred_objects: impl IntoIterator<Item = &'a dyn DeferredGeometry> + Clone,
+        forward_objects: impl IntoIterator<Item = &'a dyn Object>,
+        lights: &[&dyn Light],
+    ) -> ThreeDResult<()> {
+        pipeline.geometry_pass(camera, deferred_objects.clone())?;
+        pipeline.lighting_pass(self, camera, lights)?;
+        self.write(|| {
+            apply_effect(
+                &pipeline.context,
+                include_str!("deferred_depth_copy.frag"),
+                RenderStates {
+                    depth_test: DepthTest::Always,
+                    write_mask: WriteMask::DEPTH,
+                    ..Default::default()
+                },
+                camera.viewport(),
+                |program| program.use_depth_texture("depthMap", &pipeline.depth_texture),
+            )?;
+            for object in deferred_objects
+                .into_iter()
+                .map(|o| o as &dyn Object)
+                .filter(|o| {
+                    o.opaque_render_method() == OpaqueRenderMethod::Forward
+                        || o.material_type() == MaterialType::Transparent
+                })
+                .chain(forward_objects)
+            {
+                object.render(camera, lights);
+            }
+            Ok(())
+        })
+    }
+}
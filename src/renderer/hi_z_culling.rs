@@ -0,0 +1,302 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::collections::HashMap;
+
+///
+/// A hierarchical-Z (Hi-Z) mip pyramid built from a depth prepass, used by [cull_visible] and
+/// [render_culled] to reject [Object]s and instances that are hidden behind closer geometry without
+/// having to submit them for rendering at all.
+///
+/// Each mip level stores, per texel, the *maximum* depth (i.e. the depth of the closest surface is
+/// the smallest value, so "maximum" here means farthest) of its four parent texels, so a coarse mip
+/// texel conservatively bounds the depth of every pixel under it - sampling it answers "is anything
+/// in front of this whole screen-space region visible?" in one lookup instead of one per pixel.
+///
+pub struct HiZBuffer {
+    context: Context,
+    mip_levels: Vec<Texture2D>,
+    width: u32,
+    height: u32,
+}
+
+impl HiZBuffer {
+    ///
+    /// Builds a full Hi-Z mip pyramid from `depth_texture`, the depth buffer written by a prior
+    /// depth (or geometry) prepass. Mip 0 is a copy of `depth_texture`; each subsequent mip reduces
+    /// the previous one 2x2 -> 1 by taking the maximum (farthest) depth of the four texels.
+    ///
+    pub fn new(context: &Context, depth_texture: &DepthTexture2D) -> Self {
+        let width = depth_texture.width();
+        let height = depth_texture.height();
+        let mip_count = mip_count(width, height);
+
+        let mut mip_levels = Vec::with_capacity(mip_count);
+        mip_levels.push(copy_to_color(context, depth_texture, width, height));
+        for level in 1..mip_count {
+            let (prev_width, prev_height) = (
+                (width >> (level - 1)).max(1),
+                (height >> (level - 1)).max(1),
+            );
+            let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+            let reduced = reduce_max(context, &mip_levels[level - 1], prev_width, prev_height, w, h);
+            mip_levels.push(reduced);
+        }
+
+        Self {
+            context: context.clone(),
+            mip_levels,
+            width,
+            height,
+        }
+    }
+
+    ///
+    /// Returns the finest mip level whose texels are at least `min_texel_size` screen pixels wide
+    /// (capped at the coarsest level), so a screen-space rectangle of that size spans at most 2x2
+    /// of its texels.
+    ///
+    pub fn mip_for_texel_size(&self, min_texel_size: f32) -> usize {
+        let level = min_texel_size.max(1.0).log2().ceil() as usize;
+        level.min(self.mip_levels.len() - 1)
+    }
+
+    fn level_size(&self, level: usize) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    // Reads back an entire mip level in one GPU->CPU transfer, so [cull_visible] can test every
+    // AABB that lands on this level against the same cached buffer instead of issuing one readback
+    // per AABB.
+    fn read_level(&self, level: usize) -> Vec<f32> {
+        self.mip_levels[level].as_color_target(None).read()
+    }
+}
+
+// The farthest depth in `rect` within a mip level's cached pixel buffer, given the level's size.
+fn max_depth_in_rect(pixels: &[f32], width: u32, height: u32, rect: &ScreenRect) -> f32 {
+    let x0 = ((rect.x0 * width as f32) as u32).min(width - 1);
+    let y0 = ((rect.y0 * height as f32) as u32).min(height - 1);
+    let x1 = ((rect.x1 * width as f32) as u32).min(width - 1).max(x0);
+    let y1 = ((rect.y1 * height as f32) as u32).min(height - 1).max(y0);
+
+    let mut max_depth = 0.0f32;
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            max_depth = max_depth.max(pixels[(y * width + x) as usize]);
+        }
+    }
+    max_depth
+}
+
+// The screen-space rectangle ([0, 1] x [0, 1]) an AABB projects to, together with its nearest depth.
+struct ScreenRect {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    nearest_depth: f32,
+}
+
+fn project_to_screen(aabb: &AxisAlignedBoundingBox, camera: &Camera) -> Option<ScreenRect> {
+    if aabb.is_empty() {
+        return None;
+    }
+    let corners = aabb.corners();
+    let mut x0 = f32::MAX;
+    let mut y0 = f32::MAX;
+    let mut x1 = f32::MIN;
+    let mut y1 = f32::MIN;
+    let mut nearest_depth = f32::MAX;
+    let mut any_in_front = false;
+
+    for corner in corners {
+        let clip = camera.projection() * camera.view() * corner.extend(1.0);
+        if clip.w <= 0.0 {
+            // Behind the eye - the AABB straddles the near plane, so don't attempt to cull it
+            // from a partial projection and let the regular frustum test handle it instead.
+            return None;
+        }
+        any_in_front = true;
+        let ndc = clip.truncate() / clip.w;
+        x0 = x0.min(ndc.x);
+        x1 = x1.max(ndc.x);
+        y0 = y0.min(ndc.y);
+        y1 = y1.max(ndc.y);
+        nearest_depth = nearest_depth.min(ndc.z);
+    }
+
+    if !any_in_front {
+        return None;
+    }
+    Some(ScreenRect {
+        x0: (x0 * 0.5 + 0.5).clamp(0.0, 1.0),
+        y0: (1.0 - (y1 * 0.5 + 0.5)).clamp(0.0, 1.0),
+        x1: (x1 * 0.5 + 0.5).clamp(0.0, 1.0),
+        y1: (1.0 - (y0 * 0.5 + 0.5)).clamp(0.0, 1.0),
+        // NDC z in [-1, 1] mapped to the [0, 1] window depth the Hi-Z mips store.
+        nearest_depth: (nearest_depth * 0.5 + 0.5).clamp(0.0, 1.0),
+    })
+}
+
+// Which test a single AABB needs once it's past the cheap, GPU-free frustum/projection checks.
+enum CullTest {
+    NotVisible,
+    // The AABB straddles the near plane and can't be projected to a screen rect - let the frustum
+    // test that already ran stand in for occlusion culling instead of guessing.
+    AlwaysVisible,
+    Test { level: usize, rect: ScreenRect },
+}
+
+///
+/// Frustum- and occlusion-culls `aabbs` against `camera` and `hi_z`: an entry is visible only if its
+/// [AxisAlignedBoundingBox] both intersects the view frustum and, once projected to screen space, has
+/// a nearest depth closer than the farthest depth Hi-Z recorded for the mip level whose texel size
+/// covers its projected rectangle. Returns, for every input index, whether it survived.
+///
+/// This is the culling test shared by per-[Object] culling (see [render_culled]) and per-instance
+/// culling of an instanced draw: callers compact whatever per-instance or per-object buffer they're
+/// driving using the returned mask. Every input AABB is classified against the frustum and projected
+/// to screen space first, without touching the GPU; only then is each mip level read back - once,
+/// regardless of how many AABBs land on it - so culling a large batch costs at most one GPU->CPU
+/// readback per mip level rather than one per AABB.
+///
+pub fn cull_visible(
+    camera: &Camera,
+    hi_z: &HiZBuffer,
+    aabbs: impl IntoIterator<Item = AxisAlignedBoundingBox>,
+) -> Vec<bool> {
+    let tests: Vec<CullTest> = aabbs
+        .into_iter()
+        .map(|aabb| {
+            if !camera.in_frustum(&aabb) {
+                return CullTest::NotVisible;
+            }
+            let Some(rect) = project_to_screen(&aabb, camera) else {
+                return CullTest::AlwaysVisible;
+            };
+            let texel_size = ((rect.x1 - rect.x0) * hi_z.width as f32)
+                .max((rect.y1 - rect.y0) * hi_z.height as f32);
+            CullTest::Test {
+                level: hi_z.mip_for_texel_size(texel_size),
+                rect,
+            }
+        })
+        .collect();
+
+    let mut cached_levels: HashMap<usize, Vec<f32>> = HashMap::new();
+    for test in &tests {
+        if let CullTest::Test { level, .. } = test {
+            cached_levels
+                .entry(*level)
+                .or_insert_with(|| hi_z.read_level(*level));
+        }
+    }
+
+    tests
+        .into_iter()
+        .map(|test| match test {
+            CullTest::NotVisible => false,
+            CullTest::AlwaysVisible => true,
+            CullTest::Test { level, rect } => {
+                let (width, height) = hi_z.level_size(level);
+                let farthest = max_depth_in_rect(&cached_levels[&level], width, height, &rect);
+                rect.nearest_depth <= farthest
+            }
+        })
+        .collect()
+}
+
+///
+/// Culls `objects` against `camera` and `hi_z` with [cull_visible] and renders only the ones that
+/// survive, lit by `lights` - the entry point that actually wires Hi-Z occlusion culling into
+/// [Object::render] instead of leaving [cull_visible]'s mask for the caller to apply by hand. An
+/// instanced draw can't skip individual instances this way since they share one draw call; it should
+/// call [cull_visible] directly and compact its own per-instance transform/attribute buffers with
+/// the returned mask before issuing a single instanced draw of the survivors.
+///
+pub fn render_culled<'a>(
+    camera: &Camera,
+    hi_z: &HiZBuffer,
+    lights: &[&dyn Light],
+    objects: impl IntoIterator<Item = &'a dyn Object>,
+) {
+    let objects: Vec<&dyn Object> = objects.into_iter().collect();
+    let visible = cull_visible(camera, hi_z, objects.iter().map(|object| object.aabb()));
+    for (object, is_visible) in objects.iter().zip(visible) {
+        if is_visible {
+            object.render(camera, lights);
+        }
+    }
+}
+
+fn mip_count(width: u32, height: u32) -> usize {
+    32 - width.max(height).max(1).leading_zeros() as usize
+}
+
+fn copy_to_color(
+    context: &Context,
+    depth_texture: &DepthTexture2D,
+    width: u32,
+    height: u32,
+) -> Texture2D {
+    let color = Texture2D::new_empty::<f32>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    RenderTarget::new_color(color.as_color_target(None))
+        .write(|| {
+            apply_effect(
+                context,
+                include_str!("depth_copy.frag"),
+                RenderStates {
+                    depth_test: DepthTest::Always,
+                    ..Default::default()
+                },
+                Viewport::new_at_origo(width, height),
+                |program| program.use_depth_texture("depthMap", depth_texture),
+            )
+        })
+        .unwrap();
+    color
+}
+
+// Reduces `source` (w x h) to `(out_width, out_height)` by taking, per output texel, the maximum
+// value of the 2x2 source texels it covers (fewer at a 1-texel-wide edge, 3 along an axis of odd
+// source size for the last output texel, so the source's last row/column isn't dropped).
+fn reduce_max(
+    context: &Context,
+    source: &Texture2D,
+    _source_width: u32,
+    _source_height: u32,
+    out_width: u32,
+    out_height: u32,
+) -> Texture2D {
+    let reduced = Texture2D::new_empty::<f32>(
+        context,
+        out_width,
+        out_height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    RenderTarget::new_color(reduced.as_color_target(None))
+        .write(|| {
+            apply_effect(
+                context,
+                include_str!("hi_z_reduce.frag"),
+                RenderStates::default(),
+                Viewport::new_at_origo(out_width, out_height),
+                |program| program.use_texture("source", source),
+            )
+        })
+        .unwrap();
+    reduced
+}
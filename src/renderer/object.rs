@@ -49,6 +49,26 @@ mod bounding_box;
 #[doc(inline)]
 pub use bounding_box::*;
 
+mod path;
+#[doc(inline)]
+pub use path::*;
+
+mod deferred_pipeline;
+#[doc(inline)]
+pub use deferred_pipeline::*;
+
+mod render_graph;
+#[doc(inline)]
+pub use render_graph::*;
+
+mod hi_z_culling;
+#[doc(inline)]
+pub use hi_z_culling::*;
+
+mod path_tracer;
+#[doc(inline)]
+pub use path_tracer::*;
+
 use crate::core::*;
 use crate::renderer::*;
 
@@ -90,6 +110,16 @@ pub trait Object: Geometry {
     /// Returns the type of material applied to this object.
     ///
     fn material_type(&self) -> MaterialType;
+
+    ///
+    /// Returns whether this object should be rendered in the forward or the deferred pass
+    /// when submitted to [RenderTarget::render_deferred].
+    /// Objects with [MaterialType::Transparent] are always rendered in the forward pass
+    /// regardless of this hook, since the deferred G-buffer has no way to blend.
+    ///
+    fn opaque_render_method(&self) -> OpaqueRenderMethod {
+        OpaqueRenderMethod::Forward
+    }
 }
 
 impl<T: Object + ?Sized> Object for &T {
@@ -100,6 +130,10 @@ impl<T: Object + ?Sized> Object for &T {
     fn material_type(&self) -> MaterialType {
         (*self).material_type()
     }
+
+    fn opaque_render_method(&self) -> OpaqueRenderMethod {
+        (*self).opaque_render_method()
+    }
 }
 
 impl<T: Object + ?Sized> Object for &mut T {
@@ -110,6 +144,10 @@ impl<T: Object + ?Sized> Object for &mut T {
     fn material_type(&self) -> MaterialType {
         (**self).material_type()
     }
+
+    fn opaque_render_method(&self) -> OpaqueRenderMethod {
+        (**self).opaque_render_method()
+    }
 }
 
 impl<T: Object> Object for Box<T> {
@@ -120,6 +158,10 @@ impl<T: Object> Object for Box<T> {
     fn material_type(&self) -> MaterialType {
         self.as_ref().material_type()
     }
+
+    fn opaque_render_method(&self) -> OpaqueRenderMethod {
+        self.as_ref().opaque_render_method()
+    }
 }
 
 impl<T: Object> Object for std::rc::Rc<T> {
@@ -130,6 +172,10 @@ impl<T: Object> Object for std::rc::Rc<T> {
     fn material_type(&self) -> MaterialType {
         self.as_ref().material_type()
     }
+
+    fn opaque_render_method(&self) -> OpaqueRenderMethod {
+        self.as_ref().opaque_render_method()
+    }
 }
 
 impl<T: Object> Object for std::sync::Arc<T> {
@@ -140,6 +186,10 @@ impl<T: Object> Object for std::sync::Arc<T> {
     fn material_type(&self) -> MaterialType {
         self.as_ref().material_type()
     }
+
+    fn opaque_render_method(&self) -> OpaqueRenderMethod {
+        self.as_ref().opaque_render_method()
+    }
 }
 
 impl<T: Object> Object for std::cell::RefCell<T> {
@@ -150,6 +200,10 @@ impl<T: Object> Object for std::cell::RefCell<T> {
     fn material_type(&self) -> MaterialType {
         self.borrow().material_type()
     }
+
+    fn opaque_render_method(&self) -> OpaqueRenderMethod {
+        self.borrow().opaque_render_method()
+    }
 }
 
 impl<T: Object> Object for std::sync::RwLock<T> {
@@ -160,6 +214,10 @@ impl<T: Object> Object for std::sync::RwLock<T> {
     fn material_type(&self) -> MaterialType {
         self.read().unwrap().material_type()
     }
+
+    fn opaque_render_method(&self) -> OpaqueRenderMethod {
+        self.read().unwrap().opaque_render_method()
+    }
 }
 
 // Object2D trait
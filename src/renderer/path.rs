@@ -0,0 +1,953 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// One segment of a vector path, continuing from wherever the previous segment (or the most recent
+/// [PathSegment::MoveTo]) left off. Every point is in absolute path-space coordinates, even when
+/// [Path2D::from_svg] parsed it from a relative SVG command. Mirrors the small set of segment types
+/// SVG path data (`d="..."`) is built from, so that parser only has to translate commands one-to-one.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    /// Starts a new subpath at this point without drawing anything.
+    MoveTo(Vec2),
+    /// A straight line to this point.
+    LineTo(Vec2),
+    /// A quadratic Bézier curve through one control point to this end point.
+    QuadraticTo { control: Vec2, to: Vec2 },
+    /// A cubic Bézier curve through two control points to this end point.
+    CubicTo {
+        control1: Vec2,
+        control2: Vec2,
+        to: Vec2,
+    },
+    /// Closes the current subpath with a straight line back to its start.
+    Close,
+}
+
+///
+/// The fill rule used to decide which regions of a possibly self-intersecting or multi-contour path
+/// are considered "inside" when tessellating its fill.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    /// A point is inside if a ray from it to infinity crosses the path boundary an odd number of times.
+    EvenOdd,
+    /// A point is inside if the path winds around it a non-zero number of times, counting direction.
+    NonZero,
+}
+
+///
+/// How two consecutive stroked segments are joined.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Segments are connected with a single triangle between their outer edges.
+    Bevel,
+    /// Segments are extended to meet at a point.
+    Miter,
+    /// Segments are connected with an arc.
+    Round,
+}
+
+///
+/// How the two ends of an open stroked subpath are capped.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    /// The stroke stops exactly at the end point.
+    Butt,
+    /// The stroke is extended by half its width past the end point.
+    Square,
+    /// The stroke is capped with a half-circle.
+    Round,
+}
+
+///
+/// Style used to stroke a [Path2D] - see [Path2D::stroke_mesh].
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    /// The width of the stroke, centered on the path.
+    pub width: f32,
+    /// How consecutive segments are joined.
+    pub join: LineJoin,
+    /// How open subpaths are capped.
+    pub cap: LineCap,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+        }
+    }
+}
+
+///
+/// An arbitrary filled/stroked vector path - move-to/line-to/quadratic/cubic Bézier/close - for
+/// resolution-independent 2D content such as SVG-style shapes and scalable UI, complementing the
+/// raster [line], [rectangle] and [circle] primitives.
+///
+/// Curves are flattened to polylines within [Self::with_tolerance] of the true curve before
+/// tessellation, so [Self::fill_mesh] and [Self::stroke_mesh] only ever have to triangulate
+/// straight-edged polygons.
+///
+#[derive(Debug, Clone)]
+pub struct Path2D {
+    segments: Vec<PathSegment>,
+    tolerance: f32,
+}
+
+impl Path2D {
+    /// Creates an empty path.
+    pub fn new() -> Self {
+        Self {
+            segments: Vec::new(),
+            tolerance: 0.25,
+        }
+    }
+
+    /// Appends a segment to the path.
+    pub fn push(&mut self, segment: PathSegment) -> &mut Self {
+        self.segments.push(segment);
+        self
+    }
+
+    /// Sets the maximum screen-space distance a flattened curve is allowed to deviate from the true
+    /// Bézier curve by. Smaller values produce smoother but heavier geometry.
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance.max(1e-3);
+        self
+    }
+
+    ///
+    /// Parses an SVG path data string (the contents of a `<path d="...">` attribute) into a
+    /// [Path2D]. Supports the `M`/`L`/`Q`/`C`/`Z` commands in both absolute and relative
+    /// (lowercase) form; parsing stops at the first unsupported command (such as `H`, `V`, `S`,
+    /// `T` or `A`), and the rest of the path data is ignored.
+    ///
+    pub fn from_svg(d: &str) -> Self {
+        let mut path = Self::new();
+        let mut cursor = vec2(0.0, 0.0);
+        let mut subpath_start = cursor;
+        let mut tokens = SvgTokenizer::new(d);
+
+        while let Some(command) = tokens.next_command() {
+            let relative = command.is_ascii_lowercase();
+            match command.to_ascii_uppercase() {
+                'M' => {
+                    let p = tokens.next_point();
+                    cursor = if relative { cursor + p } else { p };
+                    subpath_start = cursor;
+                    path.push(PathSegment::MoveTo(cursor));
+                }
+                'L' => {
+                    let p = tokens.next_point();
+                    cursor = if relative { cursor + p } else { p };
+                    path.push(PathSegment::LineTo(cursor));
+                }
+                'Q' => {
+                    let c = tokens.next_point();
+                    let p = tokens.next_point();
+                    let (control, to) = if relative {
+                        (cursor + c, cursor + p)
+                    } else {
+                        (c, p)
+                    };
+                    cursor = to;
+                    path.push(PathSegment::QuadraticTo { control, to });
+                }
+                'C' => {
+                    let c1 = tokens.next_point();
+                    let c2 = tokens.next_point();
+                    let p = tokens.next_point();
+                    let (control1, control2, to) = if relative {
+                        (cursor + c1, cursor + c2, cursor + p)
+                    } else {
+                        (c1, c2, p)
+                    };
+                    cursor = to;
+                    path.push(PathSegment::CubicTo {
+                        control1,
+                        control2,
+                        to,
+                    });
+                }
+                'Z' => {
+                    cursor = subpath_start;
+                    path.push(PathSegment::Close);
+                }
+                _ => break,
+            }
+        }
+        path
+    }
+
+    // Flattens the path into a list of closed and open polylines (in the order they were declared),
+    // starting a new Vec<Vec2> at every MoveTo; Close only marks the current polyline as closed.
+    fn flatten(&self) -> Vec<(Vec<Vec2>, bool)> {
+        let mut subpaths = Vec::new();
+        let mut current: Vec<Vec2> = Vec::new();
+        let mut closed = false;
+        let mut cursor = vec2(0.0, 0.0);
+
+        let finish = |subpaths: &mut Vec<(Vec<Vec2>, bool)>, current: Vec<Vec2>, closed: bool| {
+            if current.len() >= 2 {
+                subpaths.push((current, closed));
+            }
+        };
+
+        for segment in &self.segments {
+            match *segment {
+                PathSegment::MoveTo(p) => {
+                    finish(&mut subpaths, std::mem::take(&mut current), closed);
+                    closed = false;
+                    current.push(p);
+                    cursor = p;
+                }
+                PathSegment::LineTo(p) => {
+                    current.push(p);
+                    cursor = p;
+                }
+                PathSegment::QuadraticTo { control, to } => {
+                    flatten_quadratic(cursor, control, to, self.tolerance, &mut current);
+                    cursor = to;
+                }
+                PathSegment::CubicTo {
+                    control1,
+                    control2,
+                    to,
+                } => {
+                    flatten_cubic(cursor, control1, control2, to, self.tolerance, &mut current);
+                    cursor = to;
+                }
+                PathSegment::Close => {
+                    closed = true;
+                }
+            }
+        }
+        finish(&mut subpaths, current, closed);
+        subpaths
+    }
+
+    ///
+    /// Tessellates the path's fill using ear clipping, honoring `fill_rule` to decide which
+    /// subpaths are solid and which are holes from their nesting (a subpath contained in an odd
+    /// number of others is a hole under [FillRule::EvenOdd]) or from the accumulated winding
+    /// direction of the whole chain of containers it's nested inside (zero net winding is a hole
+    /// under [FillRule::NonZero]). Each hole is bridged into its container's contour - a thin zero-area
+    /// slit connecting the two boundaries - so the result is a single simple polygon with the hole
+    /// actually carved out of it; [Shape2D] renders with no stencil buffer, so a hole has to be
+    /// missing geometry rather than counting on reversed winding plus a winding-aware fill test.
+    /// Each subpath is treated as implicitly closed, matching how SVG and most vector tools fill
+    /// open subpaths.
+    ///
+    pub fn fill_mesh(&self, fill_rule: FillRule) -> CpuMesh {
+        let polygons: Vec<Vec<Vec2>> = self
+            .flatten()
+            .into_iter()
+            .filter_map(|(mut polygon, _)| {
+                if polygon.first() == polygon.last() {
+                    polygon.pop();
+                }
+                (polygon.len() >= 3).then_some(polygon)
+            })
+            .collect();
+
+        let parents = immediate_containers(&polygons);
+        let is_hole: Vec<bool> = (0..polygons.len())
+            .map(|i| match fill_rule {
+                FillRule::EvenOdd => nesting_depth(&parents, i) % 2 == 1,
+                FillRule::NonZero => accumulated_winding(&parents, &polygons, i) == 0.0,
+            })
+            .collect();
+
+        let mut positions = Vec::new();
+        for (i, polygon) in polygons.iter().enumerate() {
+            if is_hole[i] {
+                continue;
+            }
+            let mut outer = polygon.clone();
+            let outer_ccw = signed_area(&outer) > 0.0;
+            for (j, hole) in polygons.iter().enumerate() {
+                if !is_hole[j] || parents[j] != Some(i) {
+                    continue;
+                }
+                let mut hole = hole.clone();
+                // The bridging technique below needs the hole wound opposite to its container, so
+                // the slit it cuts reads as an actual hole rather than a second solid layer.
+                if (signed_area(&hole) > 0.0) == outer_ccw {
+                    hole.reverse();
+                }
+                bridge_hole(&mut outer, &hole);
+            }
+            let triangles = ear_clip(&outer);
+            positions.extend(triangles.into_iter().map(|p| vec3(p.x, p.y, 0.0)));
+        }
+
+        CpuMesh {
+            positions: Positions::F32(positions),
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Generates stroke geometry for the path: each segment becomes a quad of `style.width`
+    /// centered on the path, consecutive segments are connected per `style.join`, and open
+    /// subpaths are capped per `style.cap`.
+    ///
+    pub fn stroke_mesh(&self, style: StrokeStyle) -> CpuMesh {
+        let subpaths = self.flatten();
+        let mut positions = Vec::new();
+        let half_width = (style.width * 0.5).max(1e-4);
+
+        for (polyline, closed) in &subpaths {
+            let mut points = polyline.clone();
+            if *closed && points.first() != points.last() {
+                points.push(points[0]);
+            }
+            for window in points.windows(2) {
+                let (a, b) = (window[0], window[1]);
+                let direction = b - a;
+                if direction.magnitude2() <= 1e-12 {
+                    continue;
+                }
+                let normal = vec2(-direction.y, direction.x).normalize() * half_width;
+                let (p0, p1, p2, p3) = (a + normal, a - normal, b - normal, b + normal);
+                positions.push(vec3(p0.x, p0.y, 0.0));
+                positions.push(vec3(p1.x, p1.y, 0.0));
+                positions.push(vec3(p2.x, p2.y, 0.0));
+                positions.push(vec3(p0.x, p0.y, 0.0));
+                positions.push(vec3(p2.x, p2.y, 0.0));
+                positions.push(vec3(p3.x, p3.y, 0.0));
+            }
+            // Join geometry fills the gap on the outer side of each turn between adjacent segment
+            // quads: one triangle for a bevel, two triangles out to the miter point for a miter
+            // (or a bevel past the miter limit), an arc fan for a round join. Caps are added
+            // separately below by push_cap. A closed subpath also gets a join at its start point.
+            let mut join_points = points.clone();
+            if *closed && points.len() > 2 {
+                join_points.push(points[1]);
+            }
+            for window in join_points.windows(3) {
+                let (prev, joint, next) = (window[0], window[1], window[2]);
+                let in_dir = (joint - prev).normalize();
+                let out_dir = (next - joint).normalize();
+                let in_normal = vec2(-in_dir.y, in_dir.x) * half_width;
+                let out_normal = vec2(-out_dir.y, out_dir.x) * half_width;
+                let turn = in_dir.x * out_dir.y - in_dir.y * out_dir.x;
+                let (a, b) = if turn >= 0.0 {
+                    (joint - in_normal, joint - out_normal)
+                } else {
+                    (joint + in_normal, joint + out_normal)
+                };
+                match style.join {
+                    LineJoin::Bevel => {
+                        positions.push(vec3(joint.x, joint.y, 0.0));
+                        positions.push(vec3(a.x, a.y, 0.0));
+                        positions.push(vec3(b.x, b.y, 0.0));
+                    }
+                    LineJoin::Miter => {
+                        // Extend both edges to their intersection, falling back to a bevel once
+                        // the miter would shoot past a reasonable length limit (near-180 degree turns).
+                        let miter = line_intersection(a, a + in_dir, b, b - out_dir);
+                        let miter_ratio = miter.map(|m| (m - joint).magnitude() / half_width);
+                        if let (Some(m), Some(ratio)) = (miter, miter_ratio) {
+                            if ratio <= 4.0 {
+                                positions.push(vec3(joint.x, joint.y, 0.0));
+                                positions.push(vec3(a.x, a.y, 0.0));
+                                positions.push(vec3(m.x, m.y, 0.0));
+                                positions.push(vec3(joint.x, joint.y, 0.0));
+                                positions.push(vec3(m.x, m.y, 0.0));
+                                positions.push(vec3(b.x, b.y, 0.0));
+                                continue;
+                            }
+                        }
+                        positions.push(vec3(joint.x, joint.y, 0.0));
+                        positions.push(vec3(a.x, a.y, 0.0));
+                        positions.push(vec3(b.x, b.y, 0.0));
+                    }
+                    LineJoin::Round => push_arc_fan(&mut positions, joint, a, b, half_width),
+                }
+            }
+
+            if !closed {
+                if let (Some(&start), Some(&second)) = (points.first(), points.get(1)) {
+                    push_cap(&mut positions, style.cap, start, start - second, half_width);
+                }
+                if let (Some(&end), Some(&second_last)) =
+                    (points.last(), points.len().checked_sub(2).and_then(|i| points.get(i)))
+                {
+                    push_cap(&mut positions, style.cap, end, end - second_last, half_width);
+                }
+            }
+        }
+
+        CpuMesh {
+            positions: Positions::F32(positions),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Path2D {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Intersection of line `a0 + t*(a1 - a0)` with line `b0 + s*(b1 - b0)`, or `None` if parallel.
+fn line_intersection(a0: Vec2, a1: Vec2, b0: Vec2, b1: Vec2) -> Option<Vec2> {
+    let d1 = a1 - a0;
+    let d2 = b1 - b0;
+    let denom = d1.x * d2.y - d1.y * d2.x;
+    if denom.abs() <= 1e-9 {
+        return None;
+    }
+    let diff = b0 - a0;
+    let t = (diff.x * d2.y - diff.y * d2.x) / denom;
+    Some(a0 + d1 * t)
+}
+
+// Approximates a round join/cap with a fan of triangles between `from` and `to`, both at `radius`
+// from `center`, in roughly 20-degree steps.
+fn push_arc_fan(positions: &mut Vec<Vec3>, center: Vec2, from: Vec2, to: Vec2, radius: f32) {
+    let start_angle = (from - center).y.atan2((from - center).x);
+    let mut end_angle = (to - center).y.atan2((to - center).x);
+    if end_angle < start_angle {
+        end_angle += std::f32::consts::TAU;
+    }
+    let steps = (((end_angle - start_angle) / (20.0f32.to_radians())).ceil() as usize).max(1);
+    let mut previous = from;
+    for step in 1..=steps {
+        let t = start_angle + (end_angle - start_angle) * (step as f32 / steps as f32);
+        let next = center + vec2(t.cos(), t.sin()) * radius;
+        positions.push(vec3(center.x, center.y, 0.0));
+        positions.push(vec3(previous.x, previous.y, 0.0));
+        positions.push(vec3(next.x, next.y, 0.0));
+        previous = next;
+    }
+}
+
+// Emits the end-cap geometry at `end`, where `outward` points away from the subpath (i.e. from the
+// second-to-last point towards `end`).
+fn push_cap(positions: &mut Vec<Vec3>, cap: LineCap, end: Vec2, outward: Vec2, half_width: f32) {
+    if outward.magnitude2() <= 1e-12 {
+        return;
+    }
+    let outward = outward.normalize();
+    let normal = vec2(-outward.y, outward.x) * half_width;
+    match cap {
+        LineCap::Butt => {}
+        LineCap::Square => {
+            let extended = end + outward * half_width;
+            positions.push(vec3((end + normal).x, (end + normal).y, 0.0));
+            positions.push(vec3((end - normal).x, (end - normal).y, 0.0));
+            positions.push(vec3((extended - normal).x, (extended - normal).y, 0.0));
+            positions.push(vec3((end + normal).x, (end + normal).y, 0.0));
+            positions.push(vec3((extended - normal).x, (extended - normal).y, 0.0));
+            positions.push(vec3((extended + normal).x, (extended + normal).y, 0.0));
+        }
+        // From -normal round to +normal, so the arc sweeps through `outward` past the end point.
+        LineCap::Round => push_arc_fan(positions, end, end - normal, end + normal, half_width),
+    }
+}
+
+fn flatten_quadratic(from: Vec2, control: Vec2, to: Vec2, tolerance: f32, out: &mut Vec<Vec2>) {
+    flatten_cubic(
+        from,
+        from + (control - from) * (2.0 / 3.0),
+        to + (control - to) * (2.0 / 3.0),
+        to,
+        tolerance,
+        out,
+    );
+}
+
+fn flatten_cubic(from: Vec2, c1: Vec2, c2: Vec2, to: Vec2, tolerance: f32, out: &mut Vec<Vec2>) {
+    // Flatness test from the de Casteljau subdivision approach: the curve is "flat enough" once its
+    // control points are within `tolerance` of the chord from `from` to `to`.
+    if is_flat(from, c1, c2, to, tolerance) {
+        out.push(to);
+        return;
+    }
+    let (left, right) = subdivide_cubic(from, c1, c2, to);
+    flatten_cubic(left.0, left.1, left.2, left.3, tolerance, out);
+    flatten_cubic(right.0, right.1, right.2, right.3, tolerance, out);
+}
+
+fn is_flat(from: Vec2, c1: Vec2, c2: Vec2, to: Vec2, tolerance: f32) -> bool {
+    distance_to_segment(c1, from, to) <= tolerance && distance_to_segment(c2, from, to) <= tolerance
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let len2 = ab.magnitude2();
+    if len2 <= 1e-12 {
+        return (p - a).magnitude();
+    }
+    let t = ((p - a).dot(ab) / len2).clamp(0.0, 1.0);
+    (p - (a + ab * t)).magnitude()
+}
+
+type CubicPoints = (Vec2, Vec2, Vec2, Vec2);
+
+fn subdivide_cubic(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2) -> (CubicPoints, CubicPoints) {
+    let p01 = (p0 + p1) * 0.5;
+    let p12 = (p1 + p2) * 0.5;
+    let p23 = (p2 + p3) * 0.5;
+    let p012 = (p01 + p12) * 0.5;
+    let p123 = (p12 + p23) * 0.5;
+    let mid = (p012 + p123) * 0.5;
+    ((p0, p01, p012, mid), (mid, p123, p23, p3))
+}
+
+fn signed_area(polygon: &[Vec2]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+    area * 0.5
+}
+
+// Ray-casts from `p` in the +x direction and counts boundary crossings to decide if it's inside
+// `polygon`, regardless of winding direction - used only to establish nesting between subpaths,
+// not for the even-odd/nonzero fill test itself (that's [Path2D::fill_mesh]'s `is_hole`).
+fn point_in_polygon(p: Vec2, polygon: &[Vec2]) -> bool {
+    let mut inside = false;
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        if (a.y > p.y) != (b.y > p.y) {
+            let x = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if x > p.x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+// For every subpath, the index of the smallest-area other subpath that contains it, or `None` if
+// it isn't nested inside any other subpath. This is what [Path2D::fill_mesh] walks to tell a hole
+// from the solid region it's cut out of, and to know which solid contour to bridge each hole into.
+fn immediate_containers(polygons: &[Vec<Vec2>]) -> Vec<Option<usize>> {
+    polygons
+        .iter()
+        .enumerate()
+        .map(|(i, polygon)| {
+            polygons
+                .iter()
+                .enumerate()
+                .filter(|&(j, container)| j != i && point_in_polygon(polygon[0], container))
+                .min_by(|&(_, a), &(_, b)| {
+                    signed_area(a).abs().partial_cmp(&signed_area(b).abs()).unwrap()
+                })
+                .map(|(j, _)| j)
+        })
+        .collect()
+}
+
+// How many containers `polygon` is nested inside, walking up through `parents`.
+fn nesting_depth(parents: &[Option<usize>], mut index: usize) -> usize {
+    let mut depth = 0;
+    while let Some(parent) = parents[index] {
+        depth += 1;
+        index = parent;
+    }
+    depth
+}
+
+// The [FillRule::NonZero] winding number at a point just inside `polygons[index]`: the sum of the
+// winding directions (+1 CCW, -1 CW) of `index` and every container it's nested inside, walking the
+// same `parents` chain [nesting_depth] does. Comparing only against the *immediate* parent's winding
+// isn't enough past two levels of nesting - under the conventional alternating-direction authoring
+// convention (a solid island inside a hole inside an outer solid, as in a ring-with-a-dot or the
+// counters of letterforms like "B"/"8"/"@"), the island is wound opposite its immediate parent (the
+// hole) and would be misclassified as a hole itself. Summing the whole chain instead cancels out each
+// hole/solid pair and leaves the island's true winding, zero only when it's genuinely inside an
+// unbalanced number of holes.
+fn accumulated_winding(parents: &[Option<usize>], polygons: &[Vec<Vec2>], mut index: usize) -> f32 {
+    let mut winding = signed_area(&polygons[index]).signum();
+    while let Some(parent) = parents[index] {
+        winding += signed_area(&polygons[parent]).signum();
+        index = parent;
+    }
+    winding
+}
+
+// Splices `hole` into `outer` as a zero-area bridge, turning a polygon-with-a-hole into the single
+// simple polygon [ear_clip] knows how to triangulate: finds the hole's rightmost vertex and the
+// nearest point on `outer`'s boundary a rightward ray from it would cross, then walks into the hole
+// and back out again at that point. `hole` must already be wound opposite to `outer`.
+fn bridge_hole(outer: &mut Vec<Vec2>, hole: &[Vec2]) {
+    if hole.len() < 3 || outer.len() < 3 {
+        return;
+    }
+    let hole_start = (0..hole.len())
+        .max_by(|&a, &b| hole[a].x.partial_cmp(&hole[b].x).unwrap())
+        .unwrap();
+    let m = hole[hole_start];
+
+    let mut bridge_index = 0;
+    let mut nearest_x = f32::MAX;
+    for i in 0..outer.len() {
+        let a = outer[i];
+        let b = outer[(i + 1) % outer.len()];
+        if (a.y > m.y) != (b.y > m.y) {
+            let x = a.x + (m.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if x >= m.x && x < nearest_x {
+                nearest_x = x;
+                bridge_index = if a.x > b.x { i } else { (i + 1) % outer.len() };
+            }
+        }
+    }
+
+    let mut merged = Vec::with_capacity(outer.len() + hole.len() + 2);
+    merged.extend_from_slice(&outer[..=bridge_index]);
+    merged.extend(
+        hole[hole_start..]
+            .iter()
+            .chain(hole[..=hole_start].iter())
+            .copied(),
+    );
+    merged.push(outer[bridge_index]);
+    merged.extend_from_slice(&outer[bridge_index + 1..]);
+    *outer = merged;
+}
+
+// Ear-clipping triangulation of a simple (non-self-intersecting) polygon, returned as a flat list
+// of triangle vertices. O(n^2), which is fine for the hand-authored or SVG-imported path sizes this
+// is meant for.
+fn ear_clip(polygon: &[Vec2]) -> Vec<Vec2> {
+    let mut indices: Vec<usize> = (0..polygon.len()).collect();
+    // Ears are found assuming counter-clockwise winding; reverse the index order otherwise so the
+    // same `is_ear` test works regardless of how the original contour wound.
+    if signed_area(polygon) < 0.0 {
+        indices.reverse();
+    }
+
+    let mut triangles = Vec::new();
+    let mut guard = 0;
+    while indices.len() > 3 && guard < polygon.len() * polygon.len() {
+        guard += 1;
+        let n = indices.len();
+        let mut ear_found = false;
+        for i in 0..n {
+            let prev = indices[(i + n - 1) % n];
+            let curr = indices[i];
+            let next = indices[(i + 1) % n];
+            if is_ear(polygon, &indices, prev, curr, next) {
+                triangles.push(polygon[prev]);
+                triangles.push(polygon[curr]);
+                triangles.push(polygon[next]);
+                indices.remove(i);
+                ear_found = true;
+                break;
+            }
+        }
+        if !ear_found {
+            // Degenerate or self-intersecting input - stop rather than loop forever.
+            break;
+        }
+    }
+    if indices.len() == 3 {
+        triangles.push(polygon[indices[0]]);
+        triangles.push(polygon[indices[1]]);
+        triangles.push(polygon[indices[2]]);
+    }
+    triangles
+}
+
+fn is_ear(polygon: &[Vec2], indices: &[usize], prev: usize, curr: usize, next: usize) -> bool {
+    let (a, b, c) = (polygon[prev], polygon[curr], polygon[next]);
+    if (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x) <= 0.0 {
+        return false;
+    }
+    indices
+        .iter()
+        .filter(|&&i| i != prev && i != curr && i != next)
+        .all(|&i| !point_in_triangle(polygon[i], a, b, c))
+}
+
+fn point_in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    let sign = |p1: Vec2, p2: Vec2, p3: Vec2| (p1.x - p3.x) * (p2.y - p3.y) - (p2.x - p3.x) * (p1.y - p3.y);
+    let d1 = sign(p, a, b);
+    let d2 = sign(p, b, c);
+    let d3 = sign(p, c, a);
+    let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_neg && has_pos)
+}
+
+// A minimal tokenizer for SVG path data: single-letter commands followed by whitespace/comma
+// separated floats, with no support for implicit repeated commands or the arc ('A') command.
+struct SvgTokenizer<'a> {
+    rest: std::str::Chars<'a>,
+}
+
+impl<'a> SvgTokenizer<'a> {
+    fn new(d: &'a str) -> Self {
+        Self { rest: d.chars() }
+    }
+
+    fn skip_separators(&mut self) {
+        let mut clone = self.rest.clone();
+        while let Some(c) = clone.next() {
+            if c.is_whitespace() || c == ',' {
+                self.rest = clone.clone();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next_command(&mut self) -> Option<char> {
+        self.skip_separators();
+        self.rest.next().filter(|c| c.is_ascii_alphabetic())
+    }
+
+    fn next_number(&mut self) -> f32 {
+        self.skip_separators();
+        let mut number = String::new();
+        let mut clone = self.rest.clone();
+        while let Some(c) = clone.next() {
+            if c.is_ascii_digit() || c == '.' || c == '-' || c == '+' {
+                number.push(c);
+                self.rest = clone.clone();
+            } else {
+                break;
+            }
+        }
+        number.parse().unwrap_or(0.0)
+    }
+
+    fn next_point(&mut self) -> Vec2 {
+        let x = self.next_number();
+        let y = self.next_number();
+        vec2(x, y)
+    }
+}
+
+///
+/// An [Object2D] rendering the fill and/or stroke of a [Path2D] with a flat color, resolution
+/// independent of the path's control points since both are re-tessellated whenever [Self::set_path]
+/// is called rather than being baked at a fixed pixel size.
+///
+pub struct Shape2D {
+    context: Context,
+    fill: Option<VertexBuffer>,
+    fill_count: u32,
+    stroke: Option<VertexBuffer>,
+    stroke_count: u32,
+    program: Program,
+    color: Srgba,
+}
+
+impl Shape2D {
+    ///
+    /// Creates a new filled and/or stroked shape from `path`. Pass `None` for `fill_rule` or
+    /// `stroke_style` to skip tessellating that part.
+    ///
+    pub fn new(
+        context: &Context,
+        path: &Path2D,
+        fill_rule: Option<FillRule>,
+        stroke_style: Option<StrokeStyle>,
+        color: Srgba,
+    ) -> Self {
+        let program = Program::from_source(
+            context,
+            include_str!("path2d.vert"),
+            include_str!("path2d.frag"),
+        )
+        .expect("path2d shader failed to compile");
+
+        let mut shape = Self {
+            context: context.clone(),
+            fill: None,
+            fill_count: 0,
+            stroke: None,
+            stroke_count: 0,
+            program,
+            color,
+        };
+        shape.set_path(path, fill_rule, stroke_style);
+        shape
+    }
+
+    /// Re-tessellates the fill and/or stroke from a new path, replacing whatever was rendered before.
+    pub fn set_path(
+        &mut self,
+        path: &Path2D,
+        fill_rule: Option<FillRule>,
+        stroke_style: Option<StrokeStyle>,
+    ) {
+        self.fill = None;
+        self.fill_count = 0;
+        self.stroke = None;
+        self.stroke_count = 0;
+        if let Some(fill_rule) = fill_rule {
+            let mesh = path.fill_mesh(fill_rule);
+            if let Positions::F32(positions) = &mesh.positions {
+                self.fill_count = positions.len() as u32;
+                self.fill = Some(VertexBuffer::new_with_data(&self.context, positions));
+            }
+        }
+        if let Some(stroke_style) = stroke_style {
+            let mesh = path.stroke_mesh(stroke_style);
+            if let Positions::F32(positions) = &mesh.positions {
+                self.stroke_count = positions.len() as u32;
+                self.stroke = Some(VertexBuffer::new_with_data(&self.context, positions));
+            }
+        }
+    }
+}
+
+impl Geometry2D for Shape2D {}
+
+impl Object2D for Shape2D {
+    fn render(&self, viewport: Viewport) {
+        let camera = camera2d(viewport);
+        self.program
+            .use_uniform("viewProjection", camera.projection() * camera.view())
+            .unwrap();
+        self.program.use_uniform("color", self.color.to_linear_srgb()).unwrap();
+
+        let render_states = RenderStates {
+            cull: Cull::None,
+            blend: Blend::TRANSPARENCY,
+            ..Default::default()
+        };
+        if let Some(fill) = &self.fill {
+            self.program
+                .use_vertex_attribute("position", fill)
+                .unwrap();
+            self.program
+                .draw_arrays(render_states, camera.viewport(), self.fill_count);
+        }
+        if let Some(stroke) = &self.stroke {
+            self.program
+                .use_vertex_attribute("position", stroke)
+                .unwrap();
+            self.program
+                .draw_arrays(render_states, camera.viewport(), self.stroke_count);
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn closed_square(corners: [Vec2; 4]) -> Path2D {
+        let mut path = Path2D::new();
+        path.push(PathSegment::MoveTo(corners[0]));
+        for corner in &corners[1..] {
+            path.push(PathSegment::LineTo(*corner));
+        }
+        path.push(PathSegment::Close);
+        path
+    }
+
+    fn positions(mesh: &CpuMesh) -> &[Vec3] {
+        match &mesh.positions {
+            Positions::F32(positions) => positions,
+            _ => panic!("fill_mesh always emits Positions::F32"),
+        }
+    }
+
+    fn mesh_contains_point(mesh: &CpuMesh, p: Vec2) -> bool {
+        positions(mesh)
+            .chunks(3)
+            .any(|t| point_in_triangle(p, t[0].truncate(), t[1].truncate(), t[2].truncate()))
+    }
+
+    #[test]
+    fn signed_area_is_positive_for_counter_clockwise_winding() {
+        let ccw = [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0), vec2(0.0, 10.0)];
+        assert!(signed_area(&ccw) > 0.0);
+        let mut cw = ccw;
+        cw.reverse();
+        assert!(signed_area(&cw) < 0.0);
+    }
+
+    #[test]
+    fn ear_clip_triangulates_a_square_into_two_triangles() {
+        let square = [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0), vec2(0.0, 10.0)];
+        let triangles = ear_clip(&square);
+        assert_eq!(triangles.len(), 6);
+    }
+
+    #[test]
+    fn even_odd_hole_is_skipped() {
+        // A ring: outer square with a smaller square hole directly inside it.
+        let mut path = Path2D::new();
+        for segment in closed_square([vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0), vec2(0.0, 10.0)])
+            .segments
+        {
+            path.push(segment);
+        }
+        for segment in closed_square([vec2(2.0, 2.0), vec2(2.0, 8.0), vec2(8.0, 8.0), vec2(8.0, 2.0)])
+            .segments
+        {
+            path.push(segment);
+        }
+        let mesh = path.fill_mesh(FillRule::EvenOdd);
+        // The hole's interior must not be covered by any fill triangle - only its boundary is
+        // bridged into the outer contour as a zero-area slit.
+        assert!(!mesh_contains_point(&mesh, vec2(5.0, 5.0)));
+    }
+
+    #[test]
+    fn nonzero_fills_an_island_nested_inside_a_hole() {
+        // Three levels of nesting with the conventional alternating winding direction: a solid
+        // outer square (CCW), a hole (CW) cut out of its middle, and a solid island (CCW again,
+        // same direction as the outer) in the middle of the hole - e.g. the counter of a "B".
+        // Comparing an island only against its immediate parent's winding (the hole) would
+        // misclassify it as a hole too and drop its geometry entirely.
+        let mut path = Path2D::new();
+        for segment in closed_square([vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0), vec2(0.0, 10.0)])
+            .segments
+        {
+            path.push(segment);
+        }
+        for segment in closed_square([vec2(2.0, 2.0), vec2(2.0, 8.0), vec2(8.0, 8.0), vec2(8.0, 2.0)])
+            .segments
+        {
+            path.push(segment);
+        }
+        for segment in closed_square([vec2(4.0, 4.0), vec2(6.0, 4.0), vec2(6.0, 6.0), vec2(4.0, 6.0)])
+            .segments
+        {
+            path.push(segment);
+        }
+        let mesh = path.fill_mesh(FillRule::NonZero);
+        assert!(mesh_contains_point(&mesh, vec2(5.0, 5.0)));
+    }
+
+    #[test]
+    fn accumulated_winding_cancels_across_a_balanced_chain() {
+        let outer = vec![vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0), vec2(0.0, 10.0)];
+        let hole = vec![vec2(2.0, 2.0), vec2(2.0, 8.0), vec2(8.0, 8.0), vec2(8.0, 2.0)];
+        let island = vec![vec2(4.0, 4.0), vec2(6.0, 4.0), vec2(6.0, 6.0), vec2(4.0, 6.0)];
+        let polygons = vec![outer, hole, island];
+        let parents = immediate_containers(&polygons);
+        // outer: no container, winding is just its own sign - never zero, so never a hole.
+        assert_ne!(accumulated_winding(&parents, &polygons, 0), 0.0);
+        // hole: outer + hole cancel out.
+        assert_eq!(accumulated_winding(&parents, &polygons, 1), 0.0);
+        // island: outer + hole + island - the outer/hole pair cancels, leaving the island's own
+        // winding, so it is solid rather than a hole.
+        assert_ne!(accumulated_winding(&parents, &polygons, 2), 0.0);
+    }
+}
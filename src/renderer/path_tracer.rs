@@ -0,0 +1,711 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::f32::consts::PI;
+
+///
+/// An offline, unbiased path-traced renderer that uses the same [Camera] as the realtime
+/// rasterizer to produce a converged, physically accurate image for ground-truth comparison or
+/// offline stills. Unlike [RenderTarget::render] it does not touch the GPU at all: the scene is
+/// given to [PathTracer::new] as world-space triangle lists, gathered once into a [Bvh], then
+/// `width * height` camera rays are traced on the CPU and the image is refined over
+/// [PathTracer::render_pass] calls instead of being produced in a single shot. It does not read
+/// [Object]s, [Geometry]s or [Light]s itself; the only light sources are emissive triangles.
+///
+pub struct PathTracer {
+    bvh: Bvh,
+    lights: Vec<EmissiveTriangle>,
+    width: u32,
+    height: u32,
+    accumulated: Vec<Vec3>,
+    passes: u32,
+}
+
+#[derive(Clone, Copy)]
+struct Vertex {
+    position: Vec3,
+    normal: Vec3,
+}
+
+#[derive(Clone)]
+struct Triangle {
+    vertices: [Vertex; 3],
+    aabb: AxisAlignedBoundingBox,
+    material: TracerMaterial,
+}
+
+///
+/// The subset of a [PhysicalMaterial]'s inputs the path tracer's BRDF sampling needs, gathered
+/// once per triangle alongside its geometry in [PathTracer::new].
+///
+#[derive(Clone, Copy)]
+pub struct TracerMaterial {
+    /// Base color / diffuse albedo.
+    pub albedo: Vec3,
+    /// 0 is fully dielectric (sampled with the diffuse lobe), 1 is fully metallic (GGX lobe only).
+    pub metallic: f32,
+    /// GGX roughness, `0` is a mirror.
+    pub roughness: f32,
+    /// Radiance emitted by the surface; non-zero makes the triangle a light sampled via NEE.
+    pub emissive: Vec3,
+}
+
+#[derive(Clone, Copy)]
+struct EmissiveTriangle {
+    triangle_index: usize,
+    area: f32,
+}
+
+impl PathTracer {
+    ///
+    /// Gathers `triangles` - each item a list of world-space positions, a matching list of normals
+    /// (three of each per triangle) and the [TracerMaterial] they share - and builds a [Bvh] over
+    /// them. Every triangle with a non-zero [TracerMaterial::emissive] becomes a light sampled via
+    /// next-event estimation; `camera` only sets the image size. Call [Self::render_pass]
+    /// repeatedly to progressively refine the image.
+    ///
+    pub fn new(
+        camera: &Camera,
+        triangles: impl IntoIterator<Item = (Vec<Vec3>, Vec<Vec3>, TracerMaterial)>,
+    ) -> Self {
+        let mut all_triangles = Vec::new();
+        for (positions, normals, material) in triangles {
+            for chunk in positions.chunks(3).zip(normals.chunks(3)) {
+                let (p, n) = chunk;
+                if p.len() < 3 || n.len() < 3 {
+                    continue;
+                }
+                let vertices = [
+                    Vertex {
+                        position: p[0],
+                        normal: n[0],
+                    },
+                    Vertex {
+                        position: p[1],
+                        normal: n[1],
+                    },
+                    Vertex {
+                        position: p[2],
+                        normal: n[2],
+                    },
+                ];
+                let aabb = AxisAlignedBoundingBox::new_with_positions(&p[0..3]);
+                all_triangles.push(Triangle {
+                    vertices,
+                    aabb,
+                    material,
+                });
+            }
+        }
+
+        // Light indices refer to the BVH's reordered triangle list, which sample_direct_light reads.
+        let bvh = Bvh::build(all_triangles);
+        let lights = bvh
+            .triangles
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.material.emissive.magnitude2() > 0.0)
+            .map(|(i, t)| EmissiveTriangle {
+                triangle_index: i,
+                area: triangle_area(&t.vertices),
+            })
+            .collect();
+
+        let viewport = camera.viewport();
+        Self {
+            bvh,
+            lights,
+            width: viewport.width,
+            height: viewport.height,
+            accumulated: vec![Vec3::zero(); (viewport.width * viewport.height) as usize],
+            passes: 0,
+        }
+    }
+
+    ///
+    /// Traces one more sample per pixel, accumulating into the running average, and returns the
+    /// current (progressively refining) image as a [CpuTexture]. Each call adds one Monte Carlo
+    /// sample per pixel: at every hit a new direction is drawn from the material's BRDF
+    /// (cosine-weighted hemisphere for a diffuse lobe, an importance-sampled GGX lobe for the
+    /// metallic/roughness lobe), the throughput is weighted by `brdf * cos(theta) / pdf`, and paths
+    /// terminate after missing geometry, when no valid direction is sampled, by Russian roulette
+    /// once they've taken a few bounces, or at a fixed maximum depth. Hitting an emissive surface
+    /// adds its emission and the path continues.
+    ///
+    pub fn render_pass(&mut self, camera: &Camera, rng_seed: u32) -> CpuTexture {
+        let mut rng = Rng::new(rng_seed ^ self.passes.wrapping_mul(0x9E3779B9));
+        self.passes += 1;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let ray = camera.view_ray((x, y), self.width, self.height);
+                let radiance = self.trace(ray, &mut rng, 0);
+                let index = (y * self.width + x) as usize;
+                let prev = self.accumulated[index];
+                self.accumulated[index] =
+                    prev + (radiance - prev) / self.passes as f32;
+            }
+        }
+
+        self.to_cpu_texture()
+    }
+
+    fn trace(&self, mut ray: Ray, rng: &mut Rng, depth: u32) -> Vec3 {
+        const MAX_DEPTH: u32 = 64;
+        let mut radiance = Vec3::zero();
+        let mut throughput = Vec3::new(1.0, 1.0, 1.0);
+
+        let mut bounce = depth;
+        loop {
+            let Some(hit) = self.bvh.intersect(&ray) else {
+                break;
+            };
+            let triangle = &self.bvh.triangles[hit.triangle_index];
+            let material = triangle.material;
+
+            radiance += throughput.mul_element_wise(material.emissive);
+            radiance += throughput.mul_element_wise(self.sample_direct_light(&hit, material, rng));
+
+            let Some((next_direction, pdf, brdf)) =
+                self.sample_bsdf(hit.normal, -ray.direction, material, rng)
+            else {
+                break;
+            };
+            if pdf <= 1e-6 || !brdf.x.is_finite() || !pdf.is_finite() {
+                break;
+            }
+            let cos_theta = next_direction.dot(hit.normal).max(0.0);
+            throughput = throughput.mul_element_wise(brdf * (cos_theta / pdf));
+
+            // Russian roulette: terminate low-throughput paths early once they've had a few
+            // bounces to converge, instead of tracing every path to a fixed (wasteful) depth.
+            bounce += 1;
+            if bounce > 3 {
+                let survival = throughput.x.max(throughput.y).max(throughput.z).clamp(0.05, 0.95);
+                if rng.next_f32() > survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+            if bounce >= MAX_DEPTH {
+                break;
+            }
+
+            ray = Ray {
+                origin: hit.position + hit.normal * 1e-4,
+                direction: next_direction,
+            };
+        }
+        radiance
+    }
+
+    // Next-event estimation: sample one emissive triangle directly instead of relying on a bounce
+    // ray to randomly find it, which converges far slower for small or distant lights.
+    fn sample_direct_light(&self, hit: &Hit, material: TracerMaterial, rng: &mut Rng) -> Vec3 {
+        if self.lights.is_empty() {
+            return Vec3::zero();
+        }
+        let light = self.lights[rng.next_index(self.lights.len())];
+        let triangle = &self.bvh.triangles[light.triangle_index];
+        let point = sample_triangle(&triangle.vertices, rng);
+        let to_light = point - hit.position;
+        let distance2 = to_light.magnitude2();
+        if distance2 <= 1e-8 {
+            return Vec3::zero();
+        }
+        let distance = distance2.sqrt();
+        let direction = to_light / distance;
+
+        let shadow_ray = Ray {
+            origin: hit.position + hit.normal * 1e-4,
+            direction,
+        };
+        if self.bvh.intersect_shadow(&shadow_ray, distance - 2e-4) {
+            return Vec3::zero();
+        }
+
+        let cos_surface = direction.dot(hit.normal).max(0.0);
+        let light_normal = triangle_normal(&triangle.vertices);
+        let cos_light = (-direction).dot(light_normal).max(0.0);
+        if cos_surface <= 0.0 || cos_light <= 0.0 {
+            return Vec3::zero();
+        }
+
+        let solid_angle_pdf = distance2 / (cos_light * light.area.max(1e-8));
+        let light_pdf = solid_angle_pdf / self.lights.len() as f32;
+        if light_pdf <= 1e-8 {
+            return Vec3::zero();
+        }
+
+        let brdf = diffuse_brdf(material);
+        triangle.material.emissive.mul_element_wise(brdf) * (cos_surface / light_pdf)
+    }
+
+    // Returns (sampled direction, pdf, brdf value) or `None` if the surface absorbs everything.
+    //
+    // The GGX and diffuse lobes are sampled as a mixture, chosen with probability `metallic` /
+    // `1 - metallic` respectively, so the pdf each branch returns must be the *mixture* pdf
+    // (selection probability times the lobe's own pdf) rather than just the lobe's pdf - otherwise
+    // `brdf * cos / pdf` in [Self::trace] doesn't divide out the same selection probability that
+    // [diffuse_brdf] already multiplies in, and diffuse energy comes out scaled by `1 - metallic`
+    // twice.
+    fn sample_bsdf(
+        &self,
+        normal: Vec3,
+        view: Vec3,
+        material: TracerMaterial,
+        rng: &mut Rng,
+    ) -> Option<(Vec3, f32, Vec3)> {
+        let specular_prob = material.metallic;
+        if rng.next_f32() < specular_prob {
+            let (direction, pdf, brdf) = sample_ggx(normal, view, material, rng)?;
+            Some((direction, pdf * specular_prob, brdf))
+        } else {
+            let direction = sample_cosine_hemisphere(normal, rng);
+            let pdf = direction.dot(normal).max(1e-6) / PI;
+            Some((direction, pdf * (1.0 - specular_prob), diffuse_brdf(material)))
+        }
+    }
+
+    fn to_cpu_texture(&self) -> CpuTexture {
+        let data: Vec<[f32; 3]> = self
+            .accumulated
+            .iter()
+            .map(|c| [c.x, c.y, c.z])
+            .collect();
+        CpuTexture {
+            data: TextureData::RgbF32(data),
+            width: self.width,
+            height: self.height,
+            ..Default::default()
+        }
+    }
+}
+
+fn diffuse_brdf(material: TracerMaterial) -> Vec3 {
+    material.albedo * ((1.0 - material.metallic) / PI)
+}
+
+fn sample_cosine_hemisphere(normal: Vec3, rng: &mut Rng) -> Vec3 {
+    let u1 = rng.next_f32();
+    let u2 = rng.next_f32();
+    let r = u1.sqrt();
+    let phi = 2.0 * PI * u2;
+    let local = Vec3::new(r * phi.cos(), r * phi.sin(), (1.0 - u1).max(0.0).sqrt());
+    to_world(local, normal)
+}
+
+// Importance-samples the GGX normal distribution for a metallic/roughness lobe, returning the
+// reflected direction, its pdf and the (Cook-Torrance) BRDF value at that direction.
+fn sample_ggx(
+    normal: Vec3,
+    view: Vec3,
+    material: TracerMaterial,
+    rng: &mut Rng,
+) -> Option<(Vec3, f32, Vec3)> {
+    let roughness = material.roughness.max(1e-3);
+    let alpha = roughness * roughness;
+    let u1 = rng.next_f32();
+    let u2 = rng.next_f32();
+    let theta = ((alpha * (u1 / (1.0 - u1)).sqrt()).atan()).min(PI * 0.5 - 1e-4);
+    let phi = 2.0 * PI * u2;
+    let local_h = Vec3::new(
+        theta.sin() * phi.cos(),
+        theta.sin() * phi.sin(),
+        theta.cos(),
+    );
+    let h = to_world(local_h, normal);
+    let direction = (2.0 * view.dot(h) * h - view).normalize();
+    if direction.dot(normal) <= 0.0 {
+        return None;
+    }
+
+    let n_dot_h = normal.dot(h).max(1e-6);
+    let n_dot_v = normal.dot(view).max(1e-6);
+    let n_dot_l = normal.dot(direction).max(1e-6);
+    let v_dot_h = view.dot(h).max(1e-6);
+
+    let d = ggx_distribution(n_dot_h, alpha);
+    let g = smith_geometry(n_dot_v, n_dot_l, alpha);
+    // F0: ~0.04 for dielectrics, tinted by albedo for metals - matching the metallic workflow the
+    // forward PhysicalMaterial already shades with, so a metal looks the same in both renderers.
+    let f0 = Vec3::new(0.04, 0.04, 0.04).lerp(material.albedo, material.metallic);
+    let specular = f0 * (d * g / (4.0 * n_dot_v * n_dot_l).max(1e-6));
+    let pdf = (d * n_dot_h / (4.0 * v_dot_h)).max(1e-6);
+    if !pdf.is_finite() {
+        return None;
+    }
+    Some((direction, pdf, specular))
+}
+
+fn ggx_distribution(n_dot_h: f32, alpha: f32) -> f32 {
+    let a2 = alpha * alpha;
+    let denom = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    a2 / (PI * denom * denom).max(1e-8)
+}
+
+fn smith_geometry(n_dot_v: f32, n_dot_l: f32, alpha: f32) -> f32 {
+    let k = alpha * alpha * 0.5;
+    let g1 = |n_dot_x: f32| n_dot_x / (n_dot_x * (1.0 - k) + k).max(1e-8);
+    g1(n_dot_v) * g1(n_dot_l)
+}
+
+fn to_world(local: Vec3, normal: Vec3) -> Vec3 {
+    let up = if normal.z.abs() < 0.999 {
+        Vec3::unit_z()
+    } else {
+        Vec3::unit_x()
+    };
+    let tangent = up.cross(normal).normalize();
+    let bitangent = normal.cross(tangent);
+    (tangent * local.x + bitangent * local.y + normal * local.z).normalize()
+}
+
+fn triangle_area(vertices: &[Vertex; 3]) -> f32 {
+    (vertices[1].position - vertices[0].position)
+        .cross(vertices[2].position - vertices[0].position)
+        .magnitude()
+        * 0.5
+}
+
+fn triangle_normal(vertices: &[Vertex; 3]) -> Vec3 {
+    (vertices[1].position - vertices[0].position)
+        .cross(vertices[2].position - vertices[0].position)
+        .normalize()
+}
+
+fn sample_triangle(vertices: &[Vertex; 3], rng: &mut Rng) -> Vec3 {
+    let mut u = rng.next_f32();
+    let mut v = rng.next_f32();
+    if u + v > 1.0 {
+        u = 1.0 - u;
+        v = 1.0 - v;
+    }
+    vertices[0].position
+        + u * (vertices[1].position - vertices[0].position)
+        + v * (vertices[2].position - vertices[0].position)
+}
+
+struct Hit {
+    position: Vec3,
+    normal: Vec3,
+    triangle_index: usize,
+}
+
+struct Ray {
+    origin: Vec3,
+    direction: Vec3,
+}
+
+// A flat BVH over [Triangle]s, built once in [PathTracer::new] and traversed for every camera and
+// shadow ray. Bounds each triangle with an [AxisAlignedBoundingBox] computed from its positions in
+// [PathTracer::new], the same bounding-box type each [Geometry] already exposes.
+struct Bvh {
+    triangles: Vec<Triangle>,
+    nodes: Vec<BvhNode>,
+}
+
+struct BvhNode {
+    aabb: AxisAlignedBoundingBox,
+    // Leaf: `first..first+count` into `triangles`. Interior: the left child is the node right
+    // after this one and `first` is the right child index; `count == 0` marks an interior node.
+    first: usize,
+    count: usize,
+}
+
+impl Bvh {
+    fn build(mut triangles: Vec<Triangle>) -> Self {
+        let mut nodes = Vec::new();
+        if triangles.is_empty() {
+            return Self { triangles, nodes };
+        }
+        let mut indices: Vec<usize> = (0..triangles.len()).collect();
+        Self::build_recursive(&mut triangles, &mut indices, 0, indices.len(), &mut nodes);
+
+        // Reorder the triangles to match the leaf ranges the recursive build computed indices for.
+        let reordered = indices.iter().map(|&i| triangles[i].clone()).collect();
+        Self {
+            triangles: reordered,
+            nodes,
+        }
+    }
+
+    fn build_recursive(
+        triangles: &mut [Triangle],
+        indices: &mut [usize],
+        start: usize,
+        end: usize,
+        nodes: &mut Vec<BvhNode>,
+    ) -> usize {
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for &i in &indices[start..end] {
+            aabb = aabb + triangles[i].aabb.clone();
+        }
+
+        const LEAF_SIZE: usize = 4;
+        if end - start <= LEAF_SIZE {
+            nodes.push(BvhNode {
+                aabb,
+                first: start,
+                count: end - start,
+            });
+            return nodes.len() - 1;
+        }
+
+        let extent = aabb.max() - aabb.min();
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        indices[start..end].sort_by(|&a, &b| {
+            let center = |i: usize| {
+                let c = triangles[i].aabb.center();
+                [c.x, c.y, c.z][axis]
+            };
+            center(a).partial_cmp(&center(b)).unwrap()
+        });
+        let mid = start + (end - start) / 2;
+
+        let node_index = nodes.len();
+        nodes.push(BvhNode {
+            aabb,
+            first: 0,
+            count: 0,
+        });
+        let left = Self::build_recursive(triangles, indices, start, mid, nodes);
+        let right = Self::build_recursive(triangles, indices, mid, end, nodes);
+        debug_assert_eq!(left, node_index + 1);
+        nodes[node_index].first = right;
+        node_index
+    }
+
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut closest: Option<(f32, Hit)> = None;
+        self.intersect_node(0, ray, f32::MAX, &mut closest);
+        closest.map(|(_, hit)| hit)
+    }
+
+    fn intersect_shadow(&self, ray: &Ray, max_distance: f32) -> bool {
+        if self.nodes.is_empty() {
+            return false;
+        }
+        self.any_hit_node(0, ray, max_distance)
+    }
+
+    fn intersect_node(&self, node_index: usize, ray: &Ray, max_t: f32, closest: &mut Option<(f32, Hit)>) {
+        let node = &self.nodes[node_index];
+        if !ray_intersects_aabb(ray, &node.aabb, max_t) {
+            return;
+        }
+        if node.count > 0 {
+            for i in node.first..node.first + node.count {
+                if let Some((t, hit)) = intersect_triangle(ray, &self.triangles[i], i) {
+                    if closest.as_ref().map_or(true, |(best_t, _)| t < *best_t) {
+                        *closest = Some((t, hit));
+                    }
+                }
+            }
+        } else {
+            self.intersect_node(node_index + 1, ray, max_t, closest);
+            self.intersect_node(node.first, ray, max_t, closest);
+        }
+    }
+
+    fn any_hit_node(&self, node_index: usize, ray: &Ray, max_distance: f32) -> bool {
+        let node = &self.nodes[node_index];
+        if !ray_intersects_aabb(ray, &node.aabb, max_distance) {
+            return false;
+        }
+        if node.count > 0 {
+            self.triangles[node.first..node.first + node.count]
+                .iter()
+                .enumerate()
+                .any(|(i, triangle)| {
+                    intersect_triangle(ray, triangle, node.first + i)
+                        .is_some_and(|(t, _)| t < max_distance)
+                })
+        } else {
+            self.any_hit_node(node_index + 1, ray, max_distance)
+                || self.any_hit_node(node.first, ray, max_distance)
+        }
+    }
+}
+
+fn ray_intersects_aabb(ray: &Ray, aabb: &AxisAlignedBoundingBox, max_t: f32) -> bool {
+    let mut t_min = 0.0f32;
+    let mut t_max = max_t;
+    for axis in 0..3 {
+        let (origin, direction, lo, hi) = match axis {
+            0 => (ray.origin.x, ray.direction.x, aabb.min().x, aabb.max().x),
+            1 => (ray.origin.y, ray.direction.y, aabb.min().y, aabb.max().y),
+            _ => (ray.origin.z, ray.direction.z, aabb.min().z, aabb.max().z),
+        };
+        if direction.abs() < 1e-8 {
+            if origin < lo || origin > hi {
+                return false;
+            }
+            continue;
+        }
+        let inv = 1.0 / direction;
+        let mut t0 = (lo - origin) * inv;
+        let mut t1 = (hi - origin) * inv;
+        if t0 > t1 {
+            std::mem::swap(&mut t0, &mut t1);
+        }
+        t_min = t_min.max(t0);
+        t_max = t_max.min(t1);
+        if t_min > t_max {
+            return false;
+        }
+    }
+    true
+}
+
+fn intersect_triangle(ray: &Ray, triangle: &Triangle, triangle_index: usize) -> Option<(f32, Hit)> {
+    // Moeller-Trumbore ray/triangle intersection.
+    const EPSILON: f32 = 1e-7;
+    let [a, b, c] = triangle.vertices;
+    let edge1 = b.position - a.position;
+    let edge2 = c.position - a.position;
+    let h = ray.direction.cross(edge2);
+    let det = edge1.dot(h);
+    if det.abs() < EPSILON {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let s = ray.origin - a.position;
+    let u = s.dot(h) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = ray.direction.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(q) * inv_det;
+    if t <= EPSILON {
+        return None;
+    }
+    let w = 1.0 - u - v;
+    let normal = (w * a.normal + u * b.normal + v * c.normal).normalize();
+    Some((
+        t,
+        Hit {
+            position: ray.origin + ray.direction * t,
+            normal,
+            triangle_index,
+        },
+    ))
+}
+
+// A small, dependency-free PRNG (xorshift32) - pulling in a crate just for path tracer noise
+// isn't worth it when every sample only needs a handful of uniform floats.
+struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    fn new(seed: u32) -> Self {
+        Self {
+            state: seed | 1,
+        }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state
+    }
+
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u32() as f32 / u32::MAX as f32).clamp(0.0, 1.0 - 1e-7)
+    }
+
+    fn next_index(&mut self, len: usize) -> usize {
+        (self.next_f32() * len as f32) as usize % len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn material(albedo: Vec3, metallic: f32, roughness: f32) -> TracerMaterial {
+        TracerMaterial {
+            albedo,
+            metallic,
+            roughness,
+            emissive: Vec3::zero(),
+        }
+    }
+
+    #[test]
+    fn diffuse_brdf_is_zero_for_fully_metallic() {
+        let brdf = diffuse_brdf(material(Vec3::new(1.0, 0.5, 0.2), 1.0, 0.5));
+        assert_eq!(brdf, Vec3::zero());
+    }
+
+    #[test]
+    fn diffuse_brdf_scales_albedo_by_one_minus_metallic() {
+        let albedo = Vec3::new(0.8, 0.4, 0.2);
+        let brdf = diffuse_brdf(material(albedo, 0.25, 0.5));
+        let expected = albedo * (0.75 / PI);
+        assert!((brdf - expected).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn ggx_distribution_peaks_at_normal_incidence() {
+        let alpha = 0.25;
+        let peak = ggx_distribution(1.0, alpha);
+        let off_peak = ggx_distribution(0.5, alpha);
+        assert!(peak > off_peak);
+    }
+
+    #[test]
+    fn smith_geometry_is_one_at_grazing_free_limit() {
+        // With alpha -> 0 (a mirror), the Smith masking-shadowing term should approach 1 for any
+        // non-grazing viewing/light direction.
+        let g = smith_geometry(0.8, 0.6, 1e-4);
+        assert!((g - 1.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn sample_ggx_specular_tints_toward_albedo_for_metals() {
+        let normal = Vec3::unit_z();
+        let view = Vec3::unit_z();
+        let albedo = Vec3::new(1.0, 0.2, 0.05);
+        let mut rng = Rng::new(42);
+        let dielectric = sample_ggx(normal, view, material(albedo, 0.0, 0.5), &mut rng);
+        let mut rng = Rng::new(42);
+        let metallic = sample_ggx(normal, view, material(albedo, 1.0, 0.5), &mut rng);
+        let (_, _, dielectric_specular) = dielectric.expect("sample should succeed");
+        let (_, _, metallic_specular) = metallic.expect("sample should succeed");
+        // A fully metallic surface's specular response must follow its albedo's color ratios, not
+        // stay achromatic like a dielectric's ~0.04 F0.
+        assert!(metallic_specular.y / metallic_specular.x < dielectric_specular.y / dielectric_specular.x);
+    }
+
+    #[test]
+    fn sample_ggx_rejects_directions_below_the_surface() {
+        // A view direction at a steep grazing angle can importance-sample a microfacet whose
+        // reflection ends up below the geometric normal; that direction must be rejected rather
+        // than returned as a valid (but unphysical) sample.
+        let normal = Vec3::unit_z();
+        let view = Vec3::new(0.999, 0.0, 0.05).normalize();
+        let mut rng = Rng::new(7);
+        for _ in 0..64 {
+            if let Some((direction, _, _)) =
+                sample_ggx(normal, view, material(Vec3::new(1.0, 1.0, 1.0), 1.0, 0.9), &mut rng)
+            {
+                assert!(direction.dot(normal) > 0.0);
+            }
+        }
+    }
+}
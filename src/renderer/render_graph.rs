@@ -0,0 +1,411 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+///
+/// A transient color texture produced by one [RenderGraph] node and consumed by another.
+/// Declared through [RenderGraph::add_pass] and allocated (and aliased with other resources
+/// that are never alive at the same time) when the graph is executed with [RenderGraph::render].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ResourceId(usize);
+
+// The parts of a pass's declaration that [RenderGraph]'s graph-shape algorithms - topological
+// sort, input validation and resource-lifetime tracking - actually need: its name (for error
+// messages) and its resource wiring. Split out from [PassNode] so those algorithms operate on
+// plain data instead of being entangled with the GPU-backed callback and clear state every pass
+// also carries, which makes them straightforward to unit test.
+#[derive(Clone)]
+struct PassShape {
+    name: String,
+    inputs: Vec<ResourceId>,
+    output: Option<ResourceId>,
+    depth_output: Option<ResourceId>,
+}
+
+///
+/// One node in a [RenderGraph]: a named pass with a set of input textures (written by earlier
+/// nodes) and output textures (written by this node and consumed by later nodes). The pass itself
+/// is a closure receiving the bound input textures and the already-cleared [RenderTarget] to
+/// write into.
+///
+struct PassNode {
+    shape: PassShape,
+    clear: ClearState,
+    callback: Box<dyn Fn(&[&Texture2D], &RenderTarget) -> ThreeDResult<()>>,
+}
+
+///
+/// Declares the sequence of render passes that make up a frame - geometry, shadow, lighting,
+/// post-process - as nodes with explicit texture inputs and outputs, instead of the caller
+/// manually binding render targets and calling [Object::render] in the right order.
+///
+/// [RenderGraph::render] topologically sorts the declared nodes from their resource dependencies,
+/// allocates a transient [Texture2D] for each declared output (aliasing the backing textures of
+/// resources whose producer/consumer ranges never overlap, since most passes only read the
+/// immediately preceding outputs), and runs each node's closure with its inputs bound and its
+/// output already cleared - the binding and clearing that [DeferredPipeline] otherwise does by hand
+/// for every fixed geometry/light/copy sequence. This turns features like SSAO or bloom into
+/// self-contained nodes that can be dropped into any graph.
+///
+pub struct RenderGraph {
+    context: Context,
+    width: u32,
+    height: u32,
+    next_resource: usize,
+    nodes: Vec<PassNode>,
+    textures: HashMap<ResourceId, Texture2D>,
+}
+
+impl RenderGraph {
+    ///
+    /// Creates an empty render graph for a `width` x `height` sized frame.
+    ///
+    pub fn new(context: &Context, width: u32, height: u32) -> Self {
+        Self {
+            context: context.clone(),
+            width,
+            height,
+            next_resource: 0,
+            nodes: Vec::new(),
+            textures: HashMap::new(),
+        }
+    }
+
+    ///
+    /// Declares a new transient resource that a later [Self::add_pass] can write to (as a color or
+    /// depth output) and another can read from (as an input).
+    ///
+    pub fn new_resource(&mut self) -> ResourceId {
+        let id = ResourceId(self.next_resource);
+        self.next_resource += 1;
+        id
+    }
+
+    ///
+    /// Adds a named pass to the graph. `inputs` are color resources that must have been produced as
+    /// another pass's `output` - a resource only ever produced as a `depth_output` cannot be listed
+    /// here, since the callback only ever receives color textures; [Self::render] rejects that
+    /// combination. `output`, if given, is the color resource this pass writes into, cleared with
+    /// `clear` before the callback runs. `depth_output`, if given, is bound as the depth target for
+    /// the pass. A pass writes at most one color output - chain several passes for more, there is no
+    /// multi-render-target support yet.
+    ///
+    pub fn add_pass(
+        &mut self,
+        name: impl Into<String>,
+        inputs: Vec<ResourceId>,
+        output: Option<ResourceId>,
+        depth_output: Option<ResourceId>,
+        clear: ClearState,
+        callback: impl Fn(&[&Texture2D], &RenderTarget) -> ThreeDResult<()> + 'static,
+    ) {
+        self.nodes.push(PassNode {
+            shape: PassShape {
+                name: name.into(),
+                inputs,
+                output,
+                depth_output,
+            },
+            clear,
+            callback: Box::new(callback),
+        });
+    }
+
+    ///
+    /// Topologically sorts the declared nodes by their resource dependencies, allocates (aliasing
+    /// where lifetimes don't overlap) a transient texture per resource, and runs each node's pass
+    /// in order, binding its declared inputs and clearing its declared output beforehand.
+    ///
+    /// `keep_alive` lists resources the caller wants to read back with [Self::resource] once
+    /// rendering is done - typically the graph's final output(s). Without this, a resource that is
+    /// never read as another pass's input (because it's the last thing the graph produces) would
+    /// look unused to the aliasing pass and could be handed to an earlier, still-live resource
+    /// instead of being preserved.
+    ///
+    pub fn render(&mut self, keep_alive: &[ResourceId]) -> ThreeDResult<()> {
+        let shapes: Vec<PassShape> = self.nodes.iter().map(|node| node.shape.clone()).collect();
+        check_inputs_are_color(&shapes)?;
+        let order = topological_order(&shapes)?;
+        let lifetimes = resource_lifetimes(&shapes, &order, keep_alive);
+        let textures = self.allocate_textures(&lifetimes);
+        let mut depth_textures: HashMap<ResourceId, DepthTexture2D> = HashMap::new();
+
+        for &index in &order {
+            let node = &self.nodes[index];
+            let inputs: Vec<&Texture2D> =
+                node.shape.inputs.iter().map(|id| &textures[id]).collect();
+
+            let color_target = node.shape.output.map(|id| textures[&id].as_color_target(None));
+            let depth_target = node.shape.depth_output.map(|id| {
+                depth_textures
+                    .entry(id)
+                    .or_insert_with(|| {
+                        DepthTexture2D::new::<f32>(
+                            &self.context,
+                            self.width,
+                            self.height,
+                            Wrapping::ClampToEdge,
+                            Wrapping::ClampToEdge,
+                        )
+                    })
+                    .as_depth_target()
+            });
+
+            let target = match (color_target, depth_target) {
+                (Some(color), Some(depth)) => RenderTarget::new(color, depth),
+                (Some(color), None) => RenderTarget::new_color(color),
+                (None, Some(depth)) => RenderTarget::new_depth(depth),
+                (None, None) => {
+                    return Err(CoreError::RenderTargetCreation(format!(
+                        "render graph pass '{}' declares neither a color nor a depth output",
+                        node.shape.name
+                    )))
+                }
+            };
+
+            target
+                .clear(node.clear)
+                .write(|| (node.callback)(&inputs, &target))?;
+        }
+        self.textures = textures;
+        Ok(())
+    }
+
+    ///
+    /// Returns the texture backing a color resource after [Self::render] has run, or `None` if the
+    /// resource was never declared as an output or the graph hasn't been rendered yet. Only
+    /// resources passed to [Self::render] as `keep_alive` are guaranteed to still hold that pass's
+    /// output - any other resource's backing texture may already have been aliased by a later pass.
+    ///
+    pub fn resource(&self, id: ResourceId) -> Option<&Texture2D> {
+        self.textures.get(&id)
+    }
+
+    // Assigns each color resource a backing texture, reusing a texture for two resources whose
+    // [first, last] ranges don't overlap so the graph doesn't allocate one texture per node.
+    fn allocate_textures(
+        &self,
+        lifetimes: &HashMap<ResourceId, (usize, usize)>,
+    ) -> HashMap<ResourceId, Texture2D> {
+        let mut sorted_resources: Vec<ResourceId> = lifetimes.keys().copied().collect();
+        sorted_resources.sort_by_key(|id| lifetimes[id].0);
+
+        let mut pool: Vec<(usize, Texture2D)> = Vec::new(); // (free_from_pos, texture)
+        let mut assigned = HashMap::new();
+
+        for id in sorted_resources {
+            let (start, end) = lifetimes[&id];
+            if let Some(slot) = pool.iter().position(|(free_from, _)| *free_from <= start) {
+                let (_, texture) = pool.remove(slot);
+                assigned.insert(id, texture.clone());
+                pool.push((end + 1, texture));
+            } else {
+                let texture = Texture2D::new_empty::<[u8; 4]>(
+                    &self.context,
+                    self.width,
+                    self.height,
+                    Interpolation::Nearest,
+                    Interpolation::Nearest,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                );
+                assigned.insert(id, texture.clone());
+                pool.push((end + 1, texture));
+            }
+        }
+
+        assigned
+    }
+}
+
+// A pass's `inputs` are bound through [PassNode::callback]'s `&[&Texture2D]` parameter, which
+// can only ever hold color textures - there is no way for a callback to receive a depth texture
+// at all. Without this check, a pass listing another pass's `depth_output` as an `input` (e.g. to
+// sample a prior depth buffer for SSAO) would still pass [topological_order] - which treats
+// `output` and `depth_output` as interchangeable producers for ordering purposes - and only fail
+// later in [RenderGraph::render] with a missing-key panic indexing the color-only `textures` map.
+fn check_inputs_are_color(nodes: &[PassShape]) -> ThreeDResult<()> {
+    let depth_outputs: HashSet<ResourceId> =
+        nodes.iter().filter_map(|node| node.depth_output).collect();
+    for node in nodes {
+        for input in &node.inputs {
+            if depth_outputs.contains(input) {
+                return Err(CoreError::RenderTargetCreation(format!(
+                    "render graph pass '{}' declares a depth resource as an input, but pass \
+                     callbacks only ever receive color textures - read it back with a dedicated \
+                     depth-copy pass into a color resource instead",
+                    node.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn topological_order(nodes: &[PassShape]) -> ThreeDResult<Vec<usize>> {
+    let mut producer: HashMap<ResourceId, usize> = HashMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        for out in node.output.iter().chain(node.depth_output.iter()) {
+            producer.insert(*out, i);
+        }
+    }
+
+    let mut visited = vec![false; nodes.len()];
+    let mut visiting = vec![false; nodes.len()];
+    let mut order = Vec::with_capacity(nodes.len());
+
+    fn visit(
+        i: usize,
+        nodes: &[PassShape],
+        producer: &HashMap<ResourceId, usize>,
+        visited: &mut Vec<bool>,
+        visiting: &mut Vec<bool>,
+        order: &mut Vec<usize>,
+    ) -> ThreeDResult<()> {
+        if visited[i] {
+            return Ok(());
+        }
+        if visiting[i] {
+            return Err(CoreError::RenderTargetCreation(
+                "render graph has a cyclic dependency between passes".to_string(),
+            ));
+        }
+        visiting[i] = true;
+        for input in &nodes[i].inputs {
+            if let Some(&dep) = producer.get(input) {
+                visit(dep, nodes, producer, visited, visiting, order)?;
+            }
+        }
+        visiting[i] = false;
+        visited[i] = true;
+        order.push(i);
+        Ok(())
+    }
+
+    for i in 0..nodes.len() {
+        visit(i, nodes, &producer, &mut visited, &mut visiting, &mut order)?;
+    }
+    Ok(order)
+}
+
+// The [first, last] index (in execution order) at which each color resource is produced and
+// last consumed. Depth resources are allocated separately since they are never aliased with
+// a color texture. A resource listed in `keep_alive` has its last-consumed index pinned past
+// the end of the graph, so [RenderGraph::allocate_textures] never hands its backing texture to a
+// later resource even if nothing reads it as an input.
+fn resource_lifetimes(
+    nodes: &[PassShape],
+    order: &[usize],
+    keep_alive: &[ResourceId],
+) -> HashMap<ResourceId, (usize, usize)> {
+    let mut lifetimes: HashMap<ResourceId, (usize, usize)> = HashMap::new();
+    for (pos, &index) in order.iter().enumerate() {
+        let node = &nodes[index];
+        if let Some(out) = node.output {
+            lifetimes.entry(out).or_insert((pos, pos));
+        }
+        for &input in &node.inputs {
+            lifetimes.entry(input).and_modify(|range| range.1 = pos);
+        }
+    }
+    for id in keep_alive {
+        lifetimes
+            .entry(*id)
+            .and_modify(|range| range.1 = order.len());
+    }
+    lifetimes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(
+        name: &str,
+        inputs: Vec<ResourceId>,
+        output: Option<ResourceId>,
+        depth_output: Option<ResourceId>,
+    ) -> PassShape {
+        PassShape {
+            name: name.to_string(),
+            inputs,
+            output,
+            depth_output,
+        }
+    }
+
+    #[test]
+    fn topological_order_respects_dependencies() {
+        let a = ResourceId(0);
+        let b = ResourceId(1);
+        // Declared out of dependency order: "consume" reads what "produce" writes, so it must
+        // come after it in the sort even though it was pushed first.
+        let nodes = vec![
+            shape("consume", vec![a], Some(b), None),
+            shape("produce", vec![], Some(a), None),
+        ];
+        let order = topological_order(&nodes).unwrap();
+        let produce_pos = order.iter().position(|&i| i == 1).unwrap();
+        let consume_pos = order.iter().position(|&i| i == 0).unwrap();
+        assert!(produce_pos < consume_pos);
+    }
+
+    #[test]
+    fn topological_order_rejects_cycles() {
+        let a = ResourceId(0);
+        let b = ResourceId(1);
+        let nodes = vec![
+            shape("first", vec![b], Some(a), None),
+            shape("second", vec![a], Some(b), None),
+        ];
+        assert!(topological_order(&nodes).is_err());
+    }
+
+    #[test]
+    fn check_inputs_are_color_rejects_depth_output_as_input() {
+        let depth = ResourceId(0);
+        let nodes = vec![
+            shape("depth-prepass", vec![], None, Some(depth)),
+            shape("ssao", vec![depth], Some(ResourceId(1)), None),
+        ];
+        assert!(check_inputs_are_color(&nodes).is_err());
+    }
+
+    #[test]
+    fn check_inputs_are_color_accepts_color_output_as_input() {
+        let color = ResourceId(0);
+        let nodes = vec![
+            shape("produce", vec![], Some(color), None),
+            shape("consume", vec![color], Some(ResourceId(1)), None),
+        ];
+        assert!(check_inputs_are_color(&nodes).is_ok());
+    }
+
+    #[test]
+    fn resource_lifetime_extends_to_last_consumer() {
+        let a = ResourceId(0);
+        // "produce" writes `a` at position 0; "consume" reads it at position 1, so its lifetime
+        // must extend to cover both, not just the position it was produced at.
+        let nodes = vec![
+            shape("produce", vec![], Some(a), None),
+            shape("consume", vec![a], Some(ResourceId(1)), None),
+        ];
+        let order = vec![0usize, 1usize];
+        let lifetimes = resource_lifetimes(&nodes, &order, &[]);
+        assert_eq!(lifetimes[&a], (0, 1));
+    }
+
+    #[test]
+    fn keep_alive_pins_lifetime_past_the_last_consumer() {
+        let a = ResourceId(0);
+        let nodes = vec![shape("produce", vec![], Some(a), None)];
+        let order = vec![0usize];
+        let lifetimes = resource_lifetimes(&nodes, &order, &[a]);
+        // Nothing reads `a` as an input, so without `keep_alive` its lifetime would end where it
+        // was produced and [RenderGraph::allocate_textures] could alias it away immediately.
+        assert_eq!(lifetimes[&a], (0, order.len()));
+    }
+}
@@ -0,0 +1,273 @@
+use crate::core::*;
+use crate::renderer::*;
+
+mod marching_cubes_tables;
+use marching_cubes_tables::{EDGE_TABLE, EDGE_VERTICES, TRIANGLE_TABLE};
+
+///
+/// A regular 3D grid of scalar density values, usually raymarched directly through a
+/// [VoxelMaterial] to render volumetric data such as a signed distance field, but which can also
+/// be turned into a lit, shadow-casting surface mesh with [VoxelGrid::to_mesh].
+///
+#[derive(Clone)]
+pub struct VoxelGrid {
+    size_in_voxels: (u32, u32, u32),
+    voxel_size: f32,
+    transformation: Mat4,
+    voxels: Vec<f32>,
+}
+
+impl VoxelGrid {
+    ///
+    /// Creates a new voxel grid of `size_in_voxels.0 x size_in_voxels.1 x size_in_voxels.2` voxels,
+    /// each `voxel_size` wide, with all densities initialized to `0.0`.
+    ///
+    pub fn new(size_in_voxels: (u32, u32, u32), voxel_size: f32) -> Self {
+        let voxel_count = (size_in_voxels.0 * size_in_voxels.1 * size_in_voxels.2) as usize;
+        Self {
+            size_in_voxels,
+            voxel_size,
+            transformation: Mat4::identity(),
+            voxels: vec![0.0; voxel_count],
+        }
+    }
+
+    ///
+    /// Returns the density at voxel corner `(x, y, z)`.
+    ///
+    pub fn get(&self, x: u32, y: u32, z: u32) -> f32 {
+        self.voxels[self.index(x, y, z)]
+    }
+
+    ///
+    /// Sets the density at voxel corner `(x, y, z)`.
+    ///
+    pub fn set(&mut self, x: u32, y: u32, z: u32, density: f32) {
+        let i = self.index(x, y, z);
+        self.voxels[i] = density;
+    }
+
+    fn index(&self, x: u32, y: u32, z: u32) -> usize {
+        (z * self.size_in_voxels.1 * self.size_in_voxels.0 + y * self.size_in_voxels.0 + x) as usize
+    }
+
+    fn position_at(&self, x: u32, y: u32, z: u32) -> Vec3 {
+        (self.transformation
+            * vec4(
+                x as f32 * self.voxel_size,
+                y as f32 * self.voxel_size,
+                z as f32 * self.voxel_size,
+                1.0,
+            ))
+        .truncate()
+    }
+
+    // Density at `(x, y, z)` with out-of-range coordinates clamped to the nearest edge voxel, so
+    // the central difference at a boundary corner falls back to a one-sided difference instead of
+    // indexing out of bounds.
+    fn get_clamped(&self, x: i64, y: i64, z: i64) -> f32 {
+        let clamp = |v: i64, n: u32| v.clamp(0, n as i64 - 1) as u32;
+        self.get(
+            clamp(x, self.size_in_voxels.0),
+            clamp(y, self.size_in_voxels.1),
+            clamp(z, self.size_in_voxels.2),
+        )
+    }
+
+    // The gradient of the density field at grid corner `(x, y, z)`, estimated with central
+    // differences (one-sided at the grid boundary, via [Self::get_clamped]), in grid space.
+    fn gradient_at(&self, x: u32, y: u32, z: u32) -> Vec3 {
+        let (x, y, z) = (x as i64, y as i64, z as i64);
+        vec3(
+            self.get_clamped(x + 1, y, z) - self.get_clamped(x - 1, y, z),
+            self.get_clamped(x, y + 1, z) - self.get_clamped(x, y - 1, z),
+            self.get_clamped(x, y, z + 1) - self.get_clamped(x, y, z - 1),
+        ) / (2.0 * self.voxel_size)
+    }
+
+    // Transforms a gradient from grid space to a world-space surface normal: gradients are
+    // covectors, so they transform by the inverse-transpose of the linear part of
+    // [Self::set_transformation] rather than by `transformation` itself (same convention as the
+    // `normalMatrix` that [DeferredPipeline::geometry_pass] computes for `gbuffer_write.vert`).
+    fn transform_normal(&self, gradient: Vec3) -> Vec3 {
+        let normal_matrix = self
+            .transformation
+            .invert()
+            .unwrap_or(Mat4::identity())
+            .transpose();
+        (normal_matrix * gradient.extend(0.0))
+            .truncate()
+            .normalize()
+    }
+
+    ///
+    /// Extracts a triangle mesh from the scalar field using [marching cubes](https://en.wikipedia.org/wiki/Marching_cubes):
+    /// every cell formed by 8 neighboring voxel corners is classified into one of 256 cases by
+    /// thresholding each corner's density against `isovalue` (bit `i` of the case index is set when
+    /// corner `i` is below `isovalue`), the standard edge and triangle tables give the up to 5
+    /// triangles that approximate the surface through that cell, and each triangle vertex is placed
+    /// by linearly interpolating along the crossed edge so the surface isn't blocky:
+    /// `p = p0 + (isovalue - v0) / (v1 - v0) * (p1 - p0)`.
+    ///
+    /// Normals are estimated from the gradient of the scalar field using central differences.
+    /// Positions and normals are in world space, i.e. they already account for [VoxelGrid::set_transformation].
+    ///
+    pub fn to_mesh(&self, isovalue: f32) -> CpuMesh {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+
+        let (nx, ny, nz) = self.size_in_voxels;
+        for z in 0..nz.saturating_sub(1) {
+            for y in 0..ny.saturating_sub(1) {
+                for x in 0..nx.saturating_sub(1) {
+                    self.polygonize_cell(x, y, z, isovalue, &mut positions, &mut normals);
+                }
+            }
+        }
+
+        CpuMesh {
+            positions: Positions::F32(positions),
+            normals: Some(normals),
+            ..Default::default()
+        }
+    }
+
+    fn polygonize_cell(
+        &self,
+        x: u32,
+        y: u32,
+        z: u32,
+        isovalue: f32,
+        positions: &mut Vec<Vec3>,
+        normals: &mut Vec<Vec3>,
+    ) {
+        // The 8 corners of the cell in the standard marching-cubes winding order.
+        let corners = [
+            (x, y, z),
+            (x + 1, y, z),
+            (x + 1, y + 1, z),
+            (x, y + 1, z),
+            (x, y, z + 1),
+            (x + 1, y, z + 1),
+            (x + 1, y + 1, z + 1),
+            (x, y + 1, z + 1),
+        ];
+        let densities: [f32; 8] = corners.map(|(cx, cy, cz)| self.get(cx, cy, cz));
+        let gradients: [Vec3; 8] = corners.map(|(cx, cy, cz)| self.gradient_at(cx, cy, cz));
+
+        let mut case_index = 0u8;
+        for (i, &density) in densities.iter().enumerate() {
+            if density < isovalue {
+                case_index |= 1 << i;
+            }
+        }
+
+        // Fully inside or fully outside the isosurface - no triangles to emit.
+        if case_index == 0 || case_index == 255 {
+            return;
+        }
+
+        let edge_mask = EDGE_TABLE[case_index as usize];
+        let mut edge_points = [Vec3::zero(); 12];
+        let mut edge_normals = [Vec3::zero(); 12];
+        for edge in 0..12 {
+            if edge_mask & (1 << edge) == 0 {
+                continue;
+            }
+            let (a, b) = EDGE_VERTICES[edge];
+            let p0 = self.position_at(corners[a].0, corners[a].1, corners[a].2);
+            let p1 = self.position_at(corners[b].0, corners[b].1, corners[b].2);
+            let v0 = densities[a];
+            let v1 = densities[b];
+            let t = if (v1 - v0).abs() > f32::EPSILON {
+                (isovalue - v0) / (v1 - v0)
+            } else {
+                0.5
+            };
+            let t = t.clamp(0.0, 1.0);
+            edge_points[edge] = p0 + t * (p1 - p0);
+            // Density increases from inside (below isovalue) to outside, so the gradient already
+            // points outward - the same convention [Self::transform_normal] assumes.
+            edge_normals[edge] =
+                self.transform_normal(gradients[a] + t * (gradients[b] - gradients[a]));
+        }
+
+        for triangle in TRIANGLE_TABLE[case_index as usize].chunks(3) {
+            if triangle[0] < 0 {
+                break;
+            }
+            for &edge in triangle {
+                positions.push(edge_points[edge as usize]);
+                normals.push(edge_normals[edge as usize]);
+            }
+        }
+    }
+
+    ///
+    /// Sets the transformation applied to the grid, used both when raymarching and when
+    /// extracting a mesh with [Self::to_mesh].
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_and_triangle_tables_cover_all_256_cases_with_valid_edge_indices() {
+        assert_eq!(EDGE_TABLE.len(), 256);
+        assert_eq!(TRIANGLE_TABLE.len(), 256);
+        // Fully outside (case 0) and fully inside (case 255) the isosurface cross no edges.
+        assert_eq!(EDGE_TABLE[0], 0);
+        assert_eq!(EDGE_TABLE[255], 0);
+        for &(a, b) in &EDGE_VERTICES {
+            assert!(a < 8 && b < 8);
+        }
+    }
+
+    #[test]
+    fn to_mesh_emits_nothing_for_a_uniform_field() {
+        // Every corner is below the isovalue (case 255) - the whole grid is "inside", so there is no
+        // surface to extract.
+        let mut grid = VoxelGrid::new((2, 2, 2), 1.0);
+        for x in 0..2 {
+            for y in 0..2 {
+                for z in 0..2 {
+                    grid.set(x, y, z, 0.0);
+                }
+            }
+        }
+        let mesh = grid.to_mesh(1.0);
+        match mesh.positions {
+            Positions::F32(positions) => assert!(positions.is_empty()),
+            _ => panic!("to_mesh always emits Positions::F32"),
+        }
+    }
+
+    #[test]
+    fn to_mesh_normals_follow_the_density_gradient() {
+        // A density field that only increases along +x: every corner's true gradient is exactly
+        // (1, 0, 0), so every normal central-difference estimates should come out the same way,
+        // regardless of which edges the isosurface happens to cross.
+        let mut grid = VoxelGrid::new((2, 2, 2), 1.0);
+        for x in 0..2u32 {
+            for y in 0..2u32 {
+                for z in 0..2u32 {
+                    grid.set(x, y, z, if x == 0 { 0.0 } else { 2.0 });
+                }
+            }
+        }
+        let mesh = grid.to_mesh(1.0);
+        let normals = mesh.normals.expect("to_mesh always emits normals");
+        assert!(!normals.is_empty());
+        for normal in normals {
+            assert!((normal.magnitude() - 1.0).abs() < 1e-4);
+            assert!(normal.x > 0.9);
+            assert!(normal.y.abs() < 1e-4);
+            assert!(normal.z.abs() < 1e-4);
+        }
+    }
+}